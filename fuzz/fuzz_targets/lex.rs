@@ -0,0 +1,30 @@
+//! Lexing the second file of a map, so that spans off by the file's start show up,
+//! must only point into that file.
+
+#![no_main]
+
+use cortado::source_map::SourceMap;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|source: &str| {
+    let mut files = SourceMap::default();
+    files.add("first.cor", "x = 1\n");
+    let id = files.add("fuzz.cor", source);
+    let file = files.file(id);
+
+    let (tokens, diagnostics) = cortado::lex(file);
+
+    for ta in &tokens {
+        assert!(file.contains(ta.span), "{:?} is outside the file", ta.span);
+    }
+    for diagnostic in &diagnostics {
+        assert!(file.contains(diagnostic.primary_span));
+        assert!(diagnostic.labels.iter().all(|label| file.contains(label.span)));
+        assert!(
+            diagnostic
+                .suggestions
+                .iter()
+                .all(|suggestion| file.contains(suggestion.span))
+        );
+    }
+});
@@ -0,0 +1,25 @@
+//! Every token but the last has to move the tokenizer forward, otherwise
+//! lexing never reaches the end of input.
+
+#![no_main]
+
+use cortado::tokenizer::{Token, Tokenizer};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|source: &str| {
+    let mut tokenizer = Tokenizer::new(source);
+    let mut offset = 0;
+
+    loop {
+        let ta = tokenizer.next_token();
+        if ta.token == Token::Eof {
+            assert_eq!(ta.span.start, source.len(), "stopped before the end");
+            break;
+        }
+
+        assert!(ta.span.start >= offset, "went back to {}", ta.span.start);
+        assert!(ta.span.end > ta.span.start, "stuck at {}", ta.span.start);
+        assert!(source.is_char_boundary(ta.span.end));
+        offset = ta.span.end;
+    }
+});
@@ -0,0 +1,89 @@
+//! Error and warning codes, with the long explanations behind `cortado explain`.
+
+use crate::suggest;
+
+/// What a code means, for people who ran into it.
+pub struct Explanation {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub text: &'static str,
+}
+
+// Codes are stable: once published, a code is never reused for another problem.
+// Errors are numbered E0001 onwards, warnings W0001 onwards.
+const EXPLANATIONS: &[Explanation] = &[
+    Explanation {
+        code: "E0001",
+        title: "Unknown character",
+        text: "\
+A character that can't start any token was found outside of a string.
+
+Cortado code is made of identifiers, numbers, strings, operators and
+comments. Any other character is an error:
+
+    price = 10$
+
+Remove the character, or wrap the text into a string if it was meant to
+be one:
+
+    price = \"10$\"
+",
+    },
+    Explanation {
+        code: "E0002",
+        title: "Unterminated string",
+        text: "\
+A string is missing its closing double quote.
+
+Strings can't span several lines, so a string is unterminated when the
+end of the line or of the file comes before its closing quote:
+
+    greeting = \"hello
+    name = \"world\"
+
+Close the string on the line it starts on:
+
+    greeting = \"hello\"
+    name = \"world\"
+",
+    },
+    Explanation {
+        code: "W0001",
+        title: "Unused suppression",
+        text: "\
+A `# cortado: ignore` comment doesn't silence any warning.
+
+A comment after code silences warnings on its own line, a comment on a
+line of its own silences warnings on the next line:
+
+    x = 1  # cortado: ignore W0002
+
+    # cortado: ignore W0002
+    x = 1
+
+When the listed warnings aren't reported there, the comment is most likely
+left over from code that has changed since. Remove it, or fix the codes
+it lists. Errors can't be silenced this way.
+",
+    },
+];
+
+/// Every known code, in order.
+pub fn all() -> &'static [Explanation] {
+    EXPLANATIONS
+}
+
+/// The explanation of a code, matched regardless of case.
+pub fn explain(code: &str) -> Option<&'static Explanation> {
+    EXPLANATIONS
+        .iter()
+        .find(|explanation| explanation.code.eq_ignore_ascii_case(code))
+}
+
+/// The known code closest to a mistyped one.
+pub fn closest(code: &str) -> Option<&'static str> {
+    suggest::closest(
+        code,
+        EXPLANATIONS.iter().map(|explanation| explanation.code),
+    )
+}
@@ -0,0 +1,251 @@
+//! Problems found in code, and how they point into it.
+
+use alloc::{collections::BTreeSet, format, string::String, vec, vec::Vec};
+use core::fmt;
+
+use serde::Serialize;
+
+use crate::source_map::SourceFile;
+use crate::tokenizer::Span;
+
+/// How bad a problem is: only errors stop a script from running.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        })
+    }
+}
+
+/// Text attached to a span. A label on the primary span explains the problem itself,
+/// labels elsewhere point out related code.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A change to the source that would fix a problem: `replacement` goes in place of `span`.
+/// An empty span inserts the replacement.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Suggestion {
+    pub span: Span,
+    pub replacement: String,
+    pub message: String,
+    // Whether `cortado fix` may apply the change without asking anyone
+    pub machine_applicable: bool,
+}
+
+/// A problem found in the code, reported by any phase from lexing onwards.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Option<&'static str>,
+    pub message: String,
+    pub primary_span: Span,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, primary_span: Span) -> Diagnostic {
+        Diagnostic::new(Severity::Error, message, primary_span)
+    }
+
+    pub fn warning(message: impl Into<String>, primary_span: Span) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, message, primary_span)
+    }
+
+    fn new(severity: Severity, message: impl Into<String>, primary_span: Span) -> Diagnostic {
+        Diagnostic {
+            severity,
+            code: None,
+            message: message.into(),
+            primary_span,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Diagnostic {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Diagnostic {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Diagnostic {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_suggestion(
+        mut self,
+        span: Span,
+        replacement: impl Into<String>,
+        message: impl Into<String>,
+        machine_applicable: bool,
+    ) -> Diagnostic {
+        self.suggestions.push(Suggestion {
+            span,
+            replacement: replacement.into(),
+            message: message.into(),
+            machine_applicable,
+        });
+        self
+    }
+}
+
+/// A `# cortado: ignore W0001` comment: the warnings with these codes on `line` aren't reported.
+#[derive(Debug, Clone)]
+pub struct Suppression {
+    // The comment itself
+    pub span: Span,
+    pub line: usize,
+    pub codes: Vec<String>,
+}
+
+/// Whether any of the diagnostics is an error.
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+}
+
+/// Drops the warnings in `file` silenced by its `# cortado: ignore` comments, and warns about
+/// the comments that silence nothing. Errors can't be silenced.
+pub fn suppress(
+    diagnostics: &mut Vec<Diagnostic>,
+    suppressions: &[Suppression],
+    file: &SourceFile,
+) {
+    let mut used = vec![false; suppressions.len()];
+
+    diagnostics.retain(|diagnostic| {
+        let Some(code) = diagnostic.code else {
+            return true;
+        };
+        if diagnostic.severity != Severity::Warning {
+            return true;
+        }
+
+        let (line, _) = line_col(&file.source, file.local(diagnostic.primary_span).start);
+        let matching = suppressions.iter().position(|suppression| {
+            suppression.line == line && suppression.codes.iter().any(|c| c == code)
+        });
+
+        match matching {
+            Some(i) => {
+                used[i] = true;
+                false
+            }
+            None => true,
+        }
+    });
+
+    for (suppression, used) in suppressions.iter().zip(used) {
+        if used {
+            continue;
+        }
+
+        diagnostics.push(
+            Diagnostic::warning(
+                format!("Unused suppression of {}", suppression.codes.join(", ")),
+                suppression.span,
+            )
+            .with_code("W0001")
+            .with_label(
+                suppression.span,
+                format!("no such warning on line {}", suppression.line),
+            )
+            .with_suggestion(
+                removal(suppression.span, file),
+                "",
+                "nothing to silence",
+                true,
+            ),
+        );
+    }
+}
+
+// What to delete to remove a comment: the whole line if the comment is alone on it,
+// or the comment and the whitespace before it if it comes after code
+fn removal(comment: Span, file: &SourceFile) -> Span {
+    let local = file.local(comment);
+    let before = &file.source[..local.start];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+    let (start, end) = if before[line_start..].trim().is_empty() {
+        let rest = &file.source[local.end..];
+        (
+            line_start,
+            local.end + rest.find('\n').map_or(rest.len(), |i| i + 1),
+        )
+    } else {
+        (before.trim_end().len(), local.end)
+    };
+
+    Span {
+        start: file.start + start,
+        end: file.start + end,
+    }
+}
+
+/// Drops diagnostics reported twice, with the same code and message for the same span,
+/// and errors that carry on right where the previous one with the same code ended,
+/// like each of `$$$`, since they are all part of one problem.
+pub fn deduplicate(diagnostics: &[Diagnostic]) -> Vec<&Diagnostic> {
+    let mut kept: Vec<&Diagnostic> = Vec::new();
+    let mut seen = BTreeSet::new();
+    let mut previous: Option<&Diagnostic> = None;
+
+    for diagnostic in diagnostics {
+        let span = diagnostic.primary_span;
+        let follows = previous.is_some_and(|previous| {
+            previous.code == diagnostic.code
+                && (previous.primary_span.start..=previous.primary_span.end).contains(&span.start)
+        });
+        previous = Some(diagnostic);
+
+        let key = (
+            diagnostic.code,
+            span.start,
+            span.end,
+            diagnostic.message.as_str(),
+        );
+        if !follows && seen.insert(key) {
+            kept.push(diagnostic);
+        }
+    }
+
+    kept
+}
+
+/// 1-based line and column (in characters) of a byte offset into `source`.
+pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
@@ -0,0 +1,184 @@
+use std::cell::RefCell;
+use std::env;
+use std::fmt::Display;
+use std::io::{self, IsTerminal};
+
+use clap::ValueEnum;
+use serde_json::Value;
+
+use crate::render::{self, Renderer};
+use crate::sarif;
+use cortado::diagnostics::{self, Diagnostic, Severity};
+use cortado::source_map::SourceMap;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ColorChoice {
+    /// Use colors when printing to a terminal and NO_COLOR is not set
+    Auto,
+    /// Always use colors
+    Always,
+    /// Never use colors
+    Never,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum MessageFormat {
+    /// Source snippets with the problems underlined
+    Human,
+    /// One JSON object per line, on stdout unless the command prints its own output there
+    Json,
+    /// A SARIF log for code scanning tools, on stdout once everything is checked. Commands
+    /// that print their own output there report diagnostics as `human` instead
+    Sarif,
+}
+
+/// What `--deny-warnings`, `-D`, `-W` and `-A` make of warnings. A code given with
+/// `-A` or `-W` wins over `-D` and `--deny-warnings`, so that exceptions can be made.
+#[derive(Default)]
+pub struct Levels {
+    pub deny_warnings: bool,
+    pub deny: Vec<&'static str>,
+    pub warn: Vec<&'static str>,
+    pub allow: Vec<&'static str>,
+}
+
+impl Levels {
+    fn apply(&self, diagnostics: &mut Vec<Diagnostic>) {
+        let is = |codes: &[&str], diagnostic: &Diagnostic| {
+            diagnostic.code.is_some_and(|code| codes.contains(&code))
+        };
+
+        diagnostics.retain(|d| d.severity != Severity::Warning || !is(&self.allow, d));
+        for diagnostic in diagnostics
+            .iter_mut()
+            .filter(|d| d.severity == Severity::Warning && !is(&self.warn, d))
+        {
+            let flag = match diagnostic.code {
+                Some(code) if self.deny.contains(&code) => format!("-D {code}"),
+                _ if self.deny_warnings => "--deny-warnings".to_string(),
+                _ => continue,
+            };
+            diagnostic.severity = Severity::Error;
+            diagnostic
+                .notes
+                .push(format!("This warning is an error because of `{flag}`."));
+        }
+    }
+}
+
+/// Prints everything the CLI has to report to the user on stderr,
+/// so that all of it is styled the same way. Diagnostics in JSON and SARIF go to stdout instead,
+/// since they are meant for other programs, unless stdout is taken by the command's own output.
+pub struct Emitter {
+    renderer: Renderer,
+    format: MessageFormat,
+    // Whether the command's output is what it reports, leaving stdout free for diagnostics
+    reports: bool,
+    // How many diagnostics to print for a single script
+    limit: usize,
+    levels: Levels,
+    // SARIF is a single document, so its results are held until `finish`
+    sarif: RefCell<Vec<Value>>,
+}
+
+impl Emitter {
+    pub fn new(
+        choice: ColorChoice,
+        format: MessageFormat,
+        limit: usize,
+        levels: Levels,
+        reports: bool,
+    ) -> Emitter {
+        let color = match choice {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            // See https://no-color.org: only a non-empty NO_COLOR counts
+            ColorChoice::Auto => {
+                io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+            }
+        };
+
+        Emitter {
+            renderer: Renderer::new(color),
+            format,
+            reports,
+            limit,
+            levels,
+            sarif: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn color(&self) -> bool {
+        self.renderer.color()
+    }
+
+    pub fn error(&self, msg: impl Display) {
+        self.emit(Severity::Error, None, msg);
+    }
+
+    /// An error about a whole file, such as not being able to read it.
+    pub fn file_error(&self, file: &str, msg: impl Display) {
+        self.emit(Severity::Error, Some(file), msg);
+    }
+
+    pub fn note(&self, msg: impl Display) {
+        self.emit(Severity::Note, None, msg);
+    }
+
+    /// Makes warnings errors or drops them, as the command line asks. Done before
+    /// deciding whether a script has errors, so denied warnings fail it.
+    pub fn set_levels(&self, diagnostics: &mut Vec<Diagnostic>) {
+        self.levels.apply(diagnostics);
+    }
+
+    pub fn diagnostics(&self, files: &SourceMap, diagnostics: &[Diagnostic]) {
+        let diagnostics = diagnostics::deduplicate(diagnostics);
+
+        for diagnostic in diagnostics.iter().take(self.limit) {
+            match self.format {
+                MessageFormat::Human => {
+                    eprint!("{}", self.renderer.diagnostic(diagnostic, files))
+                }
+                MessageFormat::Json => self.print_json(render::json(diagnostic, files)),
+                MessageFormat::Sarif if self.reports => self
+                    .sarif
+                    .borrow_mut()
+                    .push(sarif::result(diagnostic, files)),
+                // A log can't go anywhere but stdout, so these are for the user after all
+                MessageFormat::Sarif => eprint!("{}", self.renderer.diagnostic(diagnostic, files)),
+            }
+        }
+
+        if diagnostics.len() > self.limit {
+            self.note(format_args!(
+                "{} more errors omitted -- raise --error-limit to see them",
+                diagnostics.len() - self.limit
+            ));
+        }
+    }
+
+    /// Prints whatever has been held back until the end of the command,
+    /// or of a round of `--watch`.
+    pub fn finish(&self) {
+        if let (MessageFormat::Sarif, true) = (self.format, self.reports) {
+            println!("{}", sarif::log(self.sarif.take()));
+        }
+    }
+
+    fn emit(&self, severity: Severity, file: Option<&str>, msg: impl Display) {
+        match self.format {
+            MessageFormat::Json => self.print_json(render::json_message(severity, file, msg)),
+            MessageFormat::Human | MessageFormat::Sarif => {
+                eprint!("{}", self.renderer.message(severity, msg))
+            }
+        }
+    }
+
+    fn print_json(&self, line: String) {
+        if self.reports {
+            println!("{line}");
+        } else {
+            eprintln!("{line}");
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Applies suggestions to source text.
+
+use alloc::{format, string::String, vec, vec::Vec};
+use core::fmt::Write;
+
+use crate::diagnostics::Suggestion;
+
+// How many unchanged lines to show around changed ones
+const CONTEXT: usize = 3;
+
+/// Applies the suggestions that are safe to apply without review, in offsets local to `source`.
+/// Returns the fixed source and how many suggestions were applied.
+pub fn apply(source: &str, suggestions: &[Suggestion]) -> (String, usize) {
+    let mut edits: Vec<&Suggestion> = suggestions
+        .iter()
+        .filter(|suggestion| suggestion.machine_applicable)
+        .collect();
+    edits.sort_by_key(|suggestion| (suggestion.span.start, suggestion.span.end));
+
+    let mut fixed = String::with_capacity(source.len());
+    let mut pos = 0;
+    let mut applied = 0;
+
+    for edit in edits {
+        // Of two overlapping edits only the first is applied, the other one
+        // will come up again when the fixed code is checked
+        if edit.span.start < pos {
+            continue;
+        }
+
+        fixed.push_str(&source[pos..edit.span.start]);
+        fixed.push_str(&edit.replacement);
+        pos = edit.span.end;
+        applied += 1;
+    }
+
+    fixed.push_str(&source[pos..]);
+    (fixed, applied)
+}
+
+/// A unified diff of the lines that differ between `old` and `new`, with three lines
+/// of context. Line endings are kept as they are, so the diff applies to files with
+/// CRLF line endings or without a final newline too.
+pub fn diff(name: &str, old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.split_inclusive('\n').collect();
+    let new: Vec<&str> = new.split_inclusive('\n').collect();
+    let edits = edits(&old, &new);
+
+    let mut out = format!("--- {name}\n+++ {name}\n");
+    let changes: Vec<usize> = (0..edits.len()).filter(|&k| edits[k].0 != ' ').collect();
+
+    // Line numbers in `old` and `new` before each edit
+    let mut before = Vec::with_capacity(edits.len());
+    let (mut old_line, mut new_line) = (0, 0);
+    for (sign, _) in &edits {
+        before.push((old_line, new_line));
+        old_line += usize::from(*sign != '+');
+        new_line += usize::from(*sign != '-');
+    }
+    before.push((old_line, new_line));
+
+    let mut k = 0;
+    while k < changes.len() {
+        // Changes whose contexts would touch go into the same hunk
+        let first = changes[k];
+        while k + 1 < changes.len() && changes[k + 1] - changes[k] <= 2 * CONTEXT + 1 {
+            k += 1;
+        }
+        let last = changes[k];
+        k += 1;
+
+        let start = first.saturating_sub(CONTEXT);
+        let end = (last + CONTEXT + 1).min(edits.len());
+        let _ = writeln!(
+            out,
+            "@@ -{} +{} @@",
+            range(before[start].0, before[end].0 - before[start].0),
+            range(before[start].1, before[end].1 - before[start].1)
+        );
+        for (sign, line) in &edits[start..end] {
+            push_line(&mut out, *sign, line);
+        }
+    }
+
+    out
+}
+
+// The lines of both texts in order, marked with ' ' if they are in both, '-' if only
+// in `old` and '+' if only in `new`. This is Myers' diff, which takes time proportional
+// to the lengths times the number of changed lines: fixes change few lines of long files.
+fn edits<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(char, &'a str)> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = n + m;
+
+    // After `d` changes, `furthest[k]` is how far into `old` a path with `d` changes can
+    // get along diagonal `k = x - y`. `trace[d]` keeps the diagonals -d..=d for the way back.
+    let mut furthest = vec![0; 2 * max as usize + 3];
+    let at = |k: isize| (k + max + 1) as usize;
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            // Either one line further down from diagonal k + 1, or one line right from k - 1
+            let mut x = if k == -d || (k != d && furthest[at(k - 1)] < furthest[at(k + 1)]) {
+                furthest[at(k + 1)]
+            } else {
+                furthest[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            furthest[at(k)] = x;
+
+            if x >= n && y >= m {
+                trace.push(furthest[at(-d)..=at(d)].to_vec());
+                break 'search;
+            }
+        }
+        trace.push(furthest[at(-d)..=at(d)].to_vec());
+    }
+
+    // Walk back from the end, one change and the equal lines after it at a time
+    let mut edits = Vec::with_capacity((n + m) as usize);
+    let (mut x, mut y) = (n, m);
+    for d in (0..trace.len() as isize).rev() {
+        let k = x - y;
+        let (previous_x, previous_y) = if d == 0 {
+            (0, 0)
+        } else {
+            let previous = &trace[d as usize - 1];
+            let reached = |k: isize| previous[(k + d - 1) as usize];
+            let previous_k = if k == -d || (k != d && reached(k - 1) < reached(k + 1)) {
+                k + 1
+            } else {
+                k - 1
+            };
+            (reached(previous_k), reached(previous_k) - previous_k)
+        };
+
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            edits.push((' ', old[x as usize]));
+        }
+        if d > 0 {
+            if x == previous_x {
+                edits.push(('+', new[previous_y as usize]));
+            } else {
+                edits.push(('-', old[previous_x as usize]));
+            }
+        }
+        (x, y) = (previous_x, previous_y);
+    }
+
+    edits.reverse();
+    edits
+}
+
+fn push_line(out: &mut String, sign: char, line: &str) {
+    out.push(sign);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+// A hunk's range in one of the files: 1-based, except that an empty range
+// is given by the line before it, 0 if it's at the start of the file
+fn range(start: usize, len: usize) -> String {
+    if len == 0 {
+        format!("{start},0")
+    } else {
+        format!("{},{len}", start + 1)
+    }
+}
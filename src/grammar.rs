@@ -0,0 +1,121 @@
+use std::fmt::Write;
+
+use clap::ValueEnum;
+use serde_json::json;
+
+use cortado::tokenizer::{KEYWORDS, OPERATORS, Token};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// A TextMate grammar in JSON, for VS Code, Sublime Text and others
+    Textmate,
+    /// A tree-sitter `grammar.js` with the tokens, to be extended with real rules
+    TreeSitter,
+}
+
+// The lexical rules not driven by a table, kept in sync with the tokenizer by hand.
+// Digits are any of `\p{N}`, like `char::is_numeric`, not just ASCII ones
+const IDENTIFIER: &str = "[[:alpha:]][[:alpha:]\\p{N}?!_-]*";
+const IDENTIFIER_CHAR: &str = "[[:alpha:]\\p{N}?!_-]";
+const NUMBER: &str = "\\p{N}[\\p{N}_]*(?:\\.[\\p{N}_]*)?";
+const STRING: &str = "\"[^\"\\n]*\"?";
+const COMMENT: &str = "#.*$";
+
+pub fn generate(format: Format) -> String {
+    match format {
+        Format::Textmate => textmate(),
+        Format::TreeSitter => tree_sitter(),
+    }
+}
+
+fn textmate() -> String {
+    let (constants, keywords): (Vec<_>, Vec<_>) = KEYWORDS
+        .iter()
+        .partition(|(_, token)| matches!(token, Token::KwTrue | Token::KwFalse));
+    let word = |words: Vec<&(&str, Token)>| {
+        let words: Vec<&str> = words.iter().map(|(word, _)| *word).collect();
+        format!(
+            "(?<!{IDENTIFIER_CHAR})(?:{})(?!{IDENTIFIER_CHAR})",
+            words.join("|")
+        )
+    };
+    let operators: Vec<String> = OPERATORS.iter().map(|(op, _)| escape(op)).collect();
+
+    let grammar = json!({
+        "$schema": "https://raw.githubusercontent.com/martinring/tmlanguage/master/tmlanguage.json",
+        "name": "Cortado",
+        "scopeName": "source.cortado",
+        "fileTypes": ["cor"],
+        "patterns": [
+            { "include": "#comment" },
+            { "include": "#string" },
+            { "include": "#constant" },
+            { "include": "#keyword" },
+            { "include": "#number" },
+            { "include": "#identifier" },
+            { "include": "#operator" },
+        ],
+        "repository": {
+            "comment": { "name": "comment.line.number-sign.cortado", "match": COMMENT },
+            "string": { "name": "string.quoted.double.cortado", "match": STRING },
+            "constant": { "name": "constant.language.boolean.cortado", "match": word(constants) },
+            "keyword": { "name": "keyword.control.cortado", "match": word(keywords) },
+            "number": { "name": "constant.numeric.cortado", "match": NUMBER },
+            "identifier": { "name": "variable.other.cortado", "match": IDENTIFIER },
+            "operator": { "name": "keyword.operator.cortado", "match": operators.join("|") },
+        },
+    });
+
+    // The grammar is a plain `json!` value, so it always serializes
+    serde_json::to_string_pretty(&grammar).unwrap_or_default() + "\n"
+}
+
+fn tree_sitter() -> String {
+    let list = |items: &[(&str, Token)]| {
+        items
+            .iter()
+            .map(|(text, _)| format!("'{text}'"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut out = String::from("// Generated by `cortado grammar --format tree-sitter`\n");
+    let _ = writeln!(out, "module.exports = grammar({{");
+    let _ = writeln!(out, "  name: 'cortado',");
+    let _ = writeln!(out, "  extras: $ => [/\\s/, $.comment],");
+    let _ = writeln!(out, "  word: $ => $.identifier,");
+    let _ = writeln!(out, "  rules: {{");
+    let _ = writeln!(
+        out,
+        "    // Replace with the real grammar, the tokens below can stay"
+    );
+    let _ = writeln!(out, "    source_file: $ => repeat($._token),");
+    let _ = writeln!(
+        out,
+        "    _token: $ => choice($.keyword, $.operator, $.number, $.string, $.identifier),"
+    );
+    let _ = writeln!(out, "    keyword: $ => choice({}),", list(KEYWORDS));
+    let _ = writeln!(out, "    operator: $ => choice({}),", list(OPERATORS));
+    let _ = writeln!(
+        out,
+        "    identifier: $ => /\\p{{L}}[\\p{{L}}\\p{{N}}?!_-]*/,"
+    );
+    let _ = writeln!(
+        out,
+        "    number: $ => /\\p{{N}}[\\p{{N}}_]*(\\.[\\p{{N}}_]*)?/,"
+    );
+    let _ = writeln!(out, "    string: $ => /\"[^\"\\n]*\"/,");
+    let _ = writeln!(out, "    comment: $ => /#.*/,");
+    let _ = writeln!(out, "  }},");
+    let _ = writeln!(out, "}});");
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| {
+            let backslash = (!c.is_alphanumeric()).then_some('\\');
+            backslash.into_iter().chain([c])
+        })
+        .collect()
+}
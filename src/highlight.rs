@@ -0,0 +1,128 @@
+use clap::ValueEnum;
+
+use cortado::tokenizer::{KEYWORDS, OPERATORS, Token, Tokenizer};
+
+const RESET: &str = "\x1B[0m";
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// An HTML fragment with `cor-*` classes to style with CSS
+    Html,
+    /// ANSI escape codes for terminals
+    Ansi,
+}
+
+#[derive(Clone, Copy)]
+enum Class {
+    Keyword,
+    Number,
+    String,
+    Operator,
+    Comment,
+    Plain,
+}
+
+impl Class {
+    // Keywords and operators come from the tokenizer's tables, so new ones are highlighted too
+    fn of(token: &Token) -> Class {
+        match token {
+            Token::Int(_) | Token::Float(_) => Class::Number,
+            Token::Str(_) => Class::String,
+            _ if KEYWORDS.iter().any(|(_, keyword)| keyword == token) => Class::Keyword,
+            _ if OPERATORS.iter().any(|(_, operator)| operator == token) => Class::Operator,
+            _ => Class::Plain,
+        }
+    }
+
+    fn html(self) -> Option<&'static str> {
+        match self {
+            Class::Keyword => Some("cor-keyword"),
+            Class::Number => Some("cor-number"),
+            Class::String => Some("cor-string"),
+            Class::Operator => Some("cor-operator"),
+            Class::Comment => Some("cor-comment"),
+            Class::Plain => None,
+        }
+    }
+
+    fn ansi(self) -> Option<&'static str> {
+        match self {
+            Class::Keyword => Some("\x1B[1;35m"),
+            Class::Number => Some("\x1B[36m"),
+            Class::String => Some("\x1B[32m"),
+            Class::Comment => Some("\x1B[90m"),
+            Class::Operator | Class::Plain => None,
+        }
+    }
+}
+
+pub fn highlight(source: &str, format: Format) -> String {
+    let pieces = classify(source);
+
+    match format {
+        Format::Html => {
+            let mut out = String::from("<pre class=\"cortado\"><code>");
+            for (class, text) in pieces {
+                match class.html() {
+                    Some(name) => {
+                        out.push_str(&format!("<span class=\"{name}\">{}</span>", escape(text)))
+                    }
+                    None => out.push_str(&escape(text)),
+                }
+            }
+            out.push_str("</code></pre>\n");
+            out
+        }
+        Format::Ansi => {
+            let mut out = String::new();
+            for (class, text) in pieces {
+                match class.ansi() {
+                    Some(style) => out.push_str(&format!("{style}{text}{RESET}")),
+                    None => out.push_str(text),
+                }
+            }
+            out
+        }
+    }
+}
+
+// Splits the whole source, including whitespace and comments, into classified pieces
+fn classify(source: &str) -> Vec<(Class, &str)> {
+    let mut pieces = Vec::new();
+    let mut tokenizer = Tokenizer::new(source);
+    let mut pos = 0;
+
+    loop {
+        let ta = tokenizer.next_token();
+        push_trivia(&source[pos..ta.span.start], &mut pieces);
+
+        match ta.token {
+            Token::Eof => break,
+            // Text the tokenizer couldn't make sense of is left as is
+            Token::Error => pieces.push((Class::Plain, &source[ta.span.start..ta.span.end])),
+            _ => pieces.push((Class::of(&ta.token), &source[ta.span.start..ta.span.end])),
+        }
+        pos = ta.span.end;
+    }
+
+    pieces
+}
+
+fn push_trivia<'a>(mut text: &'a str, pieces: &mut Vec<(Class, &'a str)>) {
+    // Between tokens there is only whitespace and comments running until the end of a line
+    while let Some(hash) = text.find('#') {
+        let end = text[hash..].find('\n').map_or(text.len(), |i| hash + i);
+        pieces.push((Class::Plain, &text[..hash]));
+        pieces.push((Class::Comment, &text[hash..end]));
+        text = &text[end..];
+    }
+
+    pieces.push((Class::Plain, text));
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,65 @@
+//! The Cortado language as a library: everything the `cortado` binary does
+//! besides talking to the terminal.
+//!
+//! Load scripts into a [`SourceMap`](source_map::SourceMap), turn them into tokens
+//! with [`lex`], and report problems from the [`Diagnostic`]s it returns:
+//!
+//! ```
+//! use cortado::source_map::SourceMap;
+//!
+//! let mut files = SourceMap::default();
+//! let id = files.add("main.cor", "\"Hello, world!\".print");
+//! let (tokens, diagnostics) = cortado::lex(files.file(id));
+//!
+//! assert_eq!(tokens.len(), 3);
+//! assert!(diagnostics.is_empty());
+//! ```
+//!
+//! Without the `std` feature, which the default `cli` feature turns on, the library
+//! is `no_std` and only needs `alloc`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod codes;
+pub mod diagnostics;
+pub mod fix;
+pub mod source_map;
+pub mod suggest;
+pub mod tokenizer;
+
+use alloc::vec::Vec;
+
+use diagnostics::Diagnostic;
+use source_map::SourceFile;
+use tokenizer::{Token, TokenAt, Tokenizer};
+use tracing::{debug, instrument};
+
+/// Lexes a whole file, collecting every diagnostic on the way. Tokens come
+/// without the errors, and the diagnostics without the warnings silenced
+/// by `# cortado: ignore` comments.
+#[instrument(level = "info", skip_all)]
+pub fn lex(file: &SourceFile) -> (Vec<TokenAt>, Vec<Diagnostic>) {
+    let mut tokenizer = Tokenizer::starting_at(&file.source, file.start);
+    let mut tokens = Vec::new();
+
+    loop {
+        let ta = tokenizer.next_token();
+        match ta.token {
+            Token::Eof => break,
+            Token::Error => {}
+            _ => tokens.push(ta),
+        }
+    }
+
+    debug!(
+        tokens = tokens.len(),
+        errors = tokenizer.error_count(),
+        "reached end of input"
+    );
+
+    let (mut diagnostics, suppressions) = tokenizer.into_diagnostics();
+    diagnostics::suppress(&mut diagnostics, &suppressions, file);
+    (tokens, diagnostics)
+}
@@ -1,14 +1,586 @@
-mod tokenizer;
-use tokenizer::Tokenizer;
-
-fn main() {
-    let mut tokenizer = Tokenizer::new("when a>-b");
-    println!("{:?}", tokenizer.next_token());
-    println!("{:?}", tokenizer.next_token());
-    println!("{:?}", tokenizer.next_token());
-    println!("{:?}", tokenizer.next_token());
-    println!("{:?}", tokenizer.next_token());
-    println!("{:?}", tokenizer.next_token());
-    println!("{:?}", tokenizer.next_token());
-    println!("{:?}", tokenizer.next_token());
+mod emitter;
+mod grammar;
+mod highlight;
+mod render;
+mod repl;
+mod sarif;
+mod scaffold;
+mod watch;
+
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::slice;
+use std::time::Instant;
+
+use clap::error::ErrorKind;
+use clap::{ArgAction, CommandFactory, Parser, Subcommand, ValueEnum, ValueHint};
+use clap_complete::Shell;
+use cortado::diagnostics::{self, Diagnostic, Suggestion};
+use cortado::source_map::{FileId, SourceFile, SourceMap};
+use cortado::tokenizer::TokenAt;
+use cortado::{codes, fix, lex};
+use emitter::{ColorChoice, Emitter, Levels, MessageFormat};
+use tracing::{Level, debug, instrument};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::fmt::time::Uptime;
+
+// A path meaning "read the script from stdin"
+const STDIN: &str = "-";
+// How code given with `-e` is referred to in diagnostics
+const EVAL: &str = "<eval>";
+// How many errors to report per script unless told otherwise
+const ERROR_LIMIT: usize = 20;
+
+/// Cortado -- compact & focused, fewer surprises, more clarity.
+#[derive(Parser)]
+#[command(name = "cortado", version, arg_required_else_help = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Evaluate CODE instead of running a subcommand
+    #[arg(short, value_name = "CODE")]
+    eval: Option<String>,
+    /// When to color diagnostics
+    #[arg(long, global = true, value_enum, default_value = "auto")]
+    color: ColorChoice,
+    /// Stop reporting errors in a script after this many
+    #[arg(long, global = true, value_name = "N", default_value_t = ERROR_LIMIT)]
+    error_limit: usize,
+    /// How to print diagnostics
+    #[arg(long, global = true, value_enum, default_value = "human")]
+    message_format: MessageFormat,
+    /// Treat every warning as an error
+    #[arg(long, global = true)]
+    deny_warnings: bool,
+    /// Treat warnings with this code as errors (can be repeated)
+    #[arg(short = 'D', long = "deny", global = true, value_name = "CODE", value_parser = warning_code)]
+    deny: Vec<&'static str>,
+    /// Keep warnings with this code as warnings, even with --deny-warnings (can be repeated)
+    #[arg(short = 'W', long = "warn", global = true, value_name = "CODE", value_parser = warning_code)]
+    warn: Vec<&'static str>,
+    /// Don't report warnings with this code (can be repeated)
+    #[arg(short = 'A', long = "allow", global = true, value_name = "CODE", value_parser = warning_code)]
+    allow: Vec<&'static str>,
+    /// Log what the compiler is doing: -v for phase timings, -vv for details
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run a Cortado script
+    Run {
+        /// Path to the script, or `-` to read it from stdin (the default when stdin is piped)
+        #[arg(value_hint = ValueHint::FilePath)]
+        file: Option<PathBuf>,
+        /// Arguments for the script, given after `--`
+        #[arg(last = true)]
+        args: Vec<String>,
+        /// Run again every time the script changes
+        #[arg(long)]
+        watch: bool,
+        /// Print how long each phase took
+        #[arg(long)]
+        time: bool,
+    },
+    /// Print the tokens of a Cortado script
+    Tokenize {
+        /// Path to the script
+        #[arg(value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+        /// How to print the tokens
+        #[arg(long, value_enum, default_value = "table")]
+        format: TokensFormat,
+    },
+    /// Print a Cortado script with syntax highlighting
+    Highlight {
+        /// Path to the script
+        #[arg(value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+        /// What to highlight the script with
+        #[arg(long, value_enum, default_value = "ansi")]
+        format: highlight::Format,
+    },
+    /// Print a syntax highlighting grammar for editors, generated from the tokenizer
+    Grammar {
+        /// Which kind of grammar to print
+        #[arg(long, value_enum, default_value = "textmate")]
+        format: grammar::Format,
+    },
+    /// Check scripts for errors without running them
+    Check {
+        /// Paths to the scripts
+        #[arg(required = true, value_hint = ValueHint::FilePath)]
+        files: Vec<PathBuf>,
+        /// Check again every time one of the scripts changes
+        #[arg(long)]
+        watch: bool,
+        /// Only report problems with this code (can be repeated); the exit status still counts all of them
+        #[arg(long, value_name = "CODE", value_parser = error_code)]
+        only: Vec<&'static str>,
+    },
+    /// Apply the fixes cortado is sure about to scripts
+    Fix {
+        /// Paths to the scripts
+        #[arg(required = true, value_hint = ValueHint::FilePath)]
+        files: Vec<PathBuf>,
+        /// Print the changes as a diff instead of writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Create a new project
+    New {
+        /// Directory to create, its name becomes the project name
+        #[arg(value_hint = ValueHint::DirPath)]
+        path: PathBuf,
+    },
+    /// Start an interactive session
+    Repl {
+        /// Load a script into the session before the first prompt (can be repeated)
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        preload: Vec<PathBuf>,
+    },
+    /// Explain an error code in detail
+    Explain {
+        /// Error code, such as E0001
+        code: String,
+    },
+    /// Print a shell completion script
+    Completions {
+        /// Shell to complete commands for
+        shell: Shell,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TokensFormat {
+    /// One token per line with its position
+    Table,
+    /// A JSON array of `{ "token": { "kind", "value" }, "line", "col", "span" }` objects
+    Json,
+}
+
+// Exit statuses are part of the CLI's interface since scripts and CI rely on them.
+// Success is 0 and clap reports usage errors with 2.
+enum Error {
+    /// Exits with 1: a script couldn't be read, the terminal misbehaved, etc.
+    Failure(String),
+    /// Exits with 3: a script doesn't lex
+    Syntax {
+        files: SourceMap,
+        diagnostics: Vec<Diagnostic>,
+    },
+    /// Exits with 4: `cortado check` found problems
+    Check(String),
+}
+
+impl Error {
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            Error::Failure(_) => ExitCode::from(1),
+            Error::Syntax { .. } => ExitCode::from(3),
+            Error::Check(_) => ExitCode::from(4),
+        }
+    }
+
+    fn emit(&self, emitter: &Emitter) {
+        match self {
+            Error::Failure(msg) | Error::Check(msg) => emitter.error(msg),
+            Error::Syntax { files, diagnostics } => emitter.diagnostics(files, diagnostics),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    // Commands whose output is their diagnostics, the others print what they make on stdout
+    let reports = match &cli.command {
+        None | Some(Command::Run { .. } | Command::Check { .. }) => true,
+        Some(Command::Fix { dry_run, .. }) => !dry_run,
+        Some(_) => false,
+    };
+    let levels = Levels {
+        deny_warnings: cli.deny_warnings,
+        deny: cli.deny,
+        warn: cli.warn,
+        allow: cli.allow,
+    };
+    let emitter = Emitter::new(
+        cli.color,
+        cli.message_format,
+        cli.error_limit,
+        levels,
+        reports,
+    );
+    init_tracing(cli.verbose, emitter.color());
+
+    // Checked here rather than by clap, which would make the global flags conflict with
+    // subcommands as well
+    let command = match (cli.command, cli.eval) {
+        (Some(command), None) => command,
+        (None, Some(code)) => return exit(&emitter, eval(&emitter, &code)),
+        (Some(_), Some(_)) => Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "'-e' cannot be used with a subcommand",
+            )
+            .exit(),
+        (None, None) => Cli::command()
+            .error(
+                ErrorKind::MissingSubcommand,
+                "either a subcommand or '-e' is required",
+            )
+            .exit(),
+    };
+
+    let result = match command {
+        // Script arguments are only collected so far: there is no evaluator to hand them to
+        Command::Run {
+            file, watch, time, ..
+        } => script_path(file).and_then(|file| {
+            if watch {
+                watch::watch(slice::from_ref(&file), || {
+                    report(&emitter, run(&emitter, &file, time));
+                })
+                .map_err(Error::Failure)
+            } else {
+                run(&emitter, &file, time)
+            }
+        }),
+        Command::Tokenize { file, format } => tokenize(&emitter, &file, format),
+        Command::Highlight { file, format } => highlight(&file, format),
+        Command::Grammar { format } => {
+            print!("{}", grammar::generate(format));
+            Ok(())
+        }
+        Command::Check { files, watch, only } => {
+            let check = || check(&emitter, &files, &only);
+            if watch {
+                watch::watch(&files, || report(&emitter, check())).map_err(Error::Failure)
+            } else {
+                check()
+            }
+        }
+        Command::Fix { files, dry_run } => fix(&emitter, &files, dry_run),
+        Command::New { path } => new_project(&path),
+        Command::Explain { code } => explain(&code),
+        Command::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "cortado", &mut io::stdout());
+            Ok(())
+        }
+        Command::Repl { preload } => repl::run(&emitter, &preload).map_err(Error::Failure),
+    };
+
+    exit(&emitter, result)
+}
+
+fn exit(emitter: &Emitter, result: Result<(), Error>) -> ExitCode {
+    let code = match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            err.emit(emitter);
+            err.exit_code()
+        }
+    };
+
+    emitter.finish();
+    code
+}
+
+fn init_tracing(verbose: u8, ansi: bool) {
+    let level = match verbose {
+        0 => return,
+        1 => Level::INFO,
+        _ => Level::DEBUG,
+    };
+
+    // Phases are spans, so closing them logs how long each one took
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(io::stderr)
+        .with_ansi(ansi)
+        .with_timer(Uptime::default())
+        .with_target(false)
+        .init();
+}
+
+// Reports the outcome of one round of `--watch`
+fn report(emitter: &Emitter, result: Result<(), Error>) {
+    if let Err(err) = result {
+        err.emit(emitter);
+    }
+    emitter.finish();
+}
+
+#[instrument(name = "file", level = "info", skip_all, fields(path = %source_name(path)))]
+fn run(emitter: &Emitter, path: &Path, time: bool) -> Result<(), Error> {
+    let start = Instant::now();
+    let source = read_source(path).map_err(Error::Failure)?;
+    let read = start.elapsed();
+    lex_source(emitter, &source_name(path), &source)?;
+    let lexed = start.elapsed();
+
+    not_evaluated(emitter, &source_name(path));
+
+    if time {
+        emitter.note(format_args!(
+            "took {:.2?} -- read {:.2?}, lex {:.2?}",
+            start.elapsed(),
+            read,
+            lexed - read
+        ));
+    }
+    Ok(())
+}
+
+fn eval(emitter: &Emitter, code: &str) -> Result<(), Error> {
+    lex_source(emitter, EVAL, code)?;
+    not_evaluated(emitter, EVAL);
+    Ok(())
+}
+
+fn not_evaluated(emitter: &Emitter, name: &str) {
+    // There is no evaluator yet, so the best we can do is make sure the code lexes
+    emitter.note(format_args!(
+        "evaluation is not implemented yet -- '{name}' was only tokenized"
+    ));
+}
+
+fn tokenize(emitter: &Emitter, path: &Path, format: TokensFormat) -> Result<(), Error> {
+    let tokens = lex_file(emitter, path)?;
+
+    match format {
+        TokensFormat::Table => {
+            for ta in tokens {
+                let pos = format!("{}:{}", ta.line, ta.col);
+                println!("{pos:<10}{:?}", ta.token);
+            }
+        }
+        TokensFormat::Json => {
+            let json =
+                serde_json::to_string_pretty(&tokens).map_err(|e| Error::Failure(e.to_string()))?;
+            println!("{json}");
+        }
+    }
+    Ok(())
+}
+
+fn highlight(path: &Path, format: highlight::Format) -> Result<(), Error> {
+    let source = read_source(path).map_err(Error::Failure)?;
+    print!("{}", highlight::highlight(&source, format));
+    Ok(())
+}
+
+fn check(emitter: &Emitter, files: &[PathBuf], only: &[&str]) -> Result<(), Error> {
+    let mut unreadable = 0;
+    let mut failed = 0;
+
+    // Lexing is the only phase there is so far, so that's all checking amounts to
+    for path in files {
+        let mut script = match load(emitter, path) {
+            Ok(script) => script,
+            Err(msg) => {
+                emitter.file_error(&source_name(path), msg);
+                unreadable += 1;
+                continue;
+            }
+        };
+
+        if diagnostics::has_errors(&script.diagnostics) {
+            failed += 1;
+        }
+
+        if !only.is_empty() {
+            script
+                .diagnostics
+                .retain(|d| d.code.is_some_and(|code| only.contains(&code)));
+        }
+        emitter.diagnostics(&script.files, &script.diagnostics);
+    }
+
+    // Not being able to check a script at all is worse than finding problems in it
+    if unreadable > 0 {
+        Err(Error::Failure(format!(
+            "{unreadable} of {} files could not be read",
+            files.len()
+        )))
+    } else if failed > 0 {
+        Err(Error::Check(format!(
+            "{failed} of {} files have errors",
+            files.len()
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn fix(emitter: &Emitter, files: &[PathBuf], dry_run: bool) -> Result<(), Error> {
+    for path in files {
+        if path == Path::new(STDIN) && !dry_run {
+            return Err(Error::Failure(
+                "cannot fix stdin in place -- pass --dry-run to see the fixes".to_string(),
+            ));
+        }
+
+        let script = load(emitter, path).map_err(Error::Failure)?;
+        let file = script.file();
+
+        let suggestions: Vec<Suggestion> = script
+            .diagnostics
+            .iter()
+            .flat_map(|diagnostic| &diagnostic.suggestions)
+            .map(|suggestion| Suggestion {
+                span: file.local(suggestion.span),
+                ..suggestion.clone()
+            })
+            .collect();
+
+        let (fixed, applied) = fix::apply(&file.source, &suggestions);
+        if applied == 0 {
+            continue;
+        }
+
+        if dry_run {
+            print!("{}", fix::diff(&file.name, &file.source, &fixed));
+        } else {
+            fs::write(path, &fixed)
+                .map_err(|e| Error::Failure(format!("cannot write '{}': {}", file.name, e)))?;
+
+            let fixes = if applied == 1 { "fix" } else { "fixes" };
+            emitter.note(format_args!("applied {applied} {fixes} to '{}'", file.name));
+        }
+    }
+
+    Ok(())
+}
+
+fn new_project(path: &Path) -> Result<(), Error> {
+    let name = scaffold::new_project(path).map_err(Error::Failure)?;
+    println!("Created project '{name}' in {}", path.display());
+    Ok(())
+}
+
+fn explain(code: &str) -> Result<(), Error> {
+    let explanation = codes::explain(code).ok_or_else(|| Error::Failure(unknown_code(code)))?;
+
+    println!("{}: {}\n", explanation.code, explanation.title);
+    print!("{}", explanation.text);
+    Ok(())
+}
+
+// Parses a code given on the command line into its canonical spelling
+fn error_code(code: &str) -> Result<&'static str, String> {
+    codes::explain(code)
+        .map(|explanation| explanation.code)
+        .ok_or_else(|| unknown_code(code))
+}
+
+// Like `error_code`, for flags that only make sense for warnings
+fn warning_code(code: &str) -> Result<&'static str, String> {
+    let code = error_code(code)?;
+    if code.starts_with('W') {
+        Ok(code)
+    } else {
+        Err(format!(
+            "{code} is an error -- only warnings can change level"
+        ))
+    }
+}
+
+fn unknown_code(code: &str) -> String {
+    match codes::closest(code) {
+        Some(closest) => format!("'{code}' is not a Cortado error code -- did you mean {closest}?"),
+        None => format!("'{code}' is not a Cortado error code"),
+    }
+}
+
+fn script_path(file: Option<PathBuf>) -> Result<PathBuf, Error> {
+    match file {
+        Some(path) => Ok(path),
+        None if !io::stdin().is_terminal() => Ok(PathBuf::from(STDIN)),
+        None => Err(Error::Failure(
+            "no script given -- pass a path, or pipe the script into stdin".to_string(),
+        )),
+    }
+}
+
+// A script loaded into a map of its own, and what lexing it found
+struct Script {
+    files: SourceMap,
+    id: FileId,
+    tokens: Vec<TokenAt>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Script {
+    fn lex(emitter: &Emitter, name: &str, source: &str) -> Script {
+        let mut files = SourceMap::default();
+        let id = files.add(name, source);
+        let (tokens, mut diagnostics) = lex(files.file(id));
+        emitter.set_levels(&mut diagnostics);
+
+        Script {
+            files,
+            id,
+            tokens,
+            diagnostics,
+        }
+    }
+
+    fn file(&self) -> &SourceFile {
+        self.files.file(self.id)
+    }
+}
+
+// Reads and lexes a script, leaving it to the caller what to do about its diagnostics
+#[instrument(name = "file", level = "info", skip_all, fields(path = %source_name(path)))]
+fn load(emitter: &Emitter, path: &Path) -> Result<Script, String> {
+    let source = read_source(path)?;
+    Ok(Script::lex(emitter, &source_name(path), &source))
+}
+
+fn lex_file(emitter: &Emitter, path: &Path) -> Result<Vec<TokenAt>, Error> {
+    let script = load(emitter, path).map_err(Error::Failure)?;
+    accept(emitter, script)
+}
+
+fn lex_source(emitter: &Emitter, name: &str, source: &str) -> Result<Vec<TokenAt>, Error> {
+    accept(emitter, Script::lex(emitter, name, source))
+}
+
+// Fails on errors, and reports warnings right away otherwise
+fn accept(emitter: &Emitter, script: Script) -> Result<Vec<TokenAt>, Error> {
+    if diagnostics::has_errors(&script.diagnostics) {
+        return Err(Error::Syntax {
+            files: script.files,
+            diagnostics: script.diagnostics,
+        });
+    }
+    emitter.diagnostics(&script.files, &script.diagnostics);
+    Ok(script.tokens)
+}
+
+fn read_source(path: &Path) -> Result<String, String> {
+    let result = if path == Path::new(STDIN) {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source).map(|_| source)
+    } else {
+        fs::read_to_string(path)
+    };
+
+    let source = result.map_err(|e| format!("cannot read '{}': {}", source_name(path), e))?;
+    debug!(bytes = source.len(), "loaded source");
+    Ok(source)
+}
+
+fn source_name(path: &Path) -> Cow<'_, str> {
+    if path == Path::new(STDIN) {
+        Cow::Borrowed("<stdin>")
+    } else {
+        path.to_string_lossy()
+    }
 }
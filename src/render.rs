@@ -0,0 +1,319 @@
+use std::collections::BTreeSet;
+use std::fmt::{Display, Write};
+
+use serde_json::{Value, json};
+
+use cortado::diagnostics::{self, Diagnostic, Severity};
+use cortado::source_map::SourceMap;
+use cortado::tokenizer::Span;
+
+const RESET: &str = "\x1B[0m";
+const BOLD: &str = "\x1B[1m";
+const GUTTER: &str = "\x1B[1;34m";
+
+const TAB_WIDTH: usize = 4;
+// How many lines to show above and below every line with a label
+const CONTEXT: usize = 1;
+
+// An underline below a line of source
+struct Mark<'a> {
+    span: Span,
+    line: usize,
+    col: usize,
+    width: usize,
+    primary: bool,
+    message: &'a str,
+}
+
+/// Turns diagnostics into text for the terminal, with source snippets and underlined spans.
+pub struct Renderer {
+    color: bool,
+}
+
+impl Renderer {
+    pub fn new(color: bool) -> Renderer {
+        Renderer { color }
+    }
+
+    pub fn color(&self) -> bool {
+        self.color
+    }
+
+    pub fn message(&self, severity: Severity, message: impl Display) -> String {
+        format!("{}: {message}\n", self.paint(style(severity), severity))
+    }
+
+    pub fn diagnostic(&self, diagnostic: &Diagnostic, files: &SourceMap) -> String {
+        let (diagnostic, name, source) = localize(diagnostic, files);
+        let diagnostic = &diagnostic;
+
+        // The final newline ends the last line rather than starting another one
+        let lines: Vec<&str> = source
+            .strip_suffix('\n')
+            .unwrap_or(source)
+            .split('\n')
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .collect();
+        let marks = marks(diagnostic, source);
+
+        let shown: BTreeSet<usize> = marks
+            .iter()
+            .flat_map(|mark| mark.line.saturating_sub(CONTEXT)..=mark.line + CONTEXT)
+            .filter(|&line| 1 <= line && line <= lines.len())
+            .collect();
+        let width = shown.last().map_or(1, |line| line.to_string().len());
+        let pad = " ".repeat(width);
+        let bar = self.paint(GUTTER, "|");
+
+        let (line, col) = diagnostics::line_col(source, diagnostic.primary_span.start);
+        let mut out = String::new();
+
+        let header = match diagnostic.code {
+            Some(code) => format!("{}[{code}]", diagnostic.severity),
+            None => diagnostic.severity.to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "{}: {}",
+            self.paint(style(diagnostic.severity), header),
+            self.paint(BOLD, &diagnostic.message)
+        );
+        let _ = writeln!(
+            out,
+            "{pad}{} {name}:{line}:{col}",
+            self.paint(GUTTER, "-->")
+        );
+        let _ = writeln!(out, "{pad} {bar}");
+
+        let mut previous = None;
+        for &line in &shown {
+            if previous.is_some_and(|previous| line > previous + 1) {
+                let _ = writeln!(out, "{}", self.paint(GUTTER, "..."));
+            }
+            previous = Some(line);
+
+            let number = self.paint(GUTTER, format!("{line:>width$} |"));
+            let _ = writeln!(out, "{number} {}", expand_tabs(lines[line - 1]));
+
+            for mark in marks.iter().filter(|mark| mark.line == line) {
+                let (symbol, style) = if mark.primary {
+                    ("^", style(diagnostic.severity))
+                } else {
+                    ("-", GUTTER)
+                };
+                let underline = format!("{} {}", symbol.repeat(mark.width), mark.message);
+
+                let _ = writeln!(
+                    out,
+                    "{pad} {bar} {}{}",
+                    " ".repeat(mark.col),
+                    self.paint(style, underline.trim_end())
+                );
+            }
+        }
+
+        if !diagnostic.notes.is_empty() || !diagnostic.suggestions.is_empty() {
+            let _ = writeln!(out, "{pad} {bar}");
+        }
+        for note in &diagnostic.notes {
+            let _ = writeln!(
+                out,
+                "{pad} {} {}: {note}",
+                self.paint(GUTTER, "="),
+                self.paint(BOLD, "note")
+            );
+        }
+        for suggestion in &diagnostic.suggestions {
+            let (line, col) = diagnostics::line_col(source, suggestion.span.start);
+            let replaced = &source[suggestion.span.start..suggestion.span.end];
+            let change = if replaced.is_empty() {
+                format!("insert `{}` at {line}:{col}", suggestion.replacement)
+            } else if suggestion.replacement.is_empty() {
+                // Removals often take the surrounding whitespace along, which isn't worth
+                // showing, so the position is where the shown text starts
+                let start = suggestion.span.start + replaced.len() - replaced.trim_start().len();
+                let (line, col) = diagnostics::line_col(source, start);
+                format!("remove `{}` at {line}:{col}", replaced.trim())
+            } else {
+                format!("replace `{replaced}` with `{}`", suggestion.replacement)
+            };
+
+            let _ = writeln!(
+                out,
+                "{pad} {} {}: {}: {change}",
+                self.paint(GUTTER, "="),
+                self.paint(BOLD, "help"),
+                suggestion.message
+            );
+        }
+
+        out.push('\n');
+        out
+    }
+
+    fn paint(&self, style: &str, text: impl Display) -> String {
+        if self.color {
+            format!("{style}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// A diagnostic as a single line of JSON, with positions resolved for tools
+/// that don't want to deal with byte offsets.
+pub fn json(diagnostic: &Diagnostic, files: &SourceMap) -> String {
+    let (diagnostic, name, source) = localize(diagnostic, files);
+    let diagnostic = &diagnostic;
+
+    let location = |span: Span| {
+        let (line, col) = diagnostics::line_col(source, span.start);
+        let (end_line, end_col) = diagnostics::line_col(source, span.end);
+        json!({
+            "start": span.start,
+            "end": span.end,
+            "line": line,
+            "col": col,
+            "end_line": end_line,
+            "end_col": end_col,
+        })
+    };
+
+    let spans: Vec<Value> = marks(diagnostic, source)
+        .into_iter()
+        .map(|mark| {
+            let mut value = location(mark.span);
+            value["primary"] = json!(mark.primary);
+            value["label"] = json!((!mark.message.is_empty()).then_some(mark.message));
+            value
+        })
+        .collect();
+    let suggestions: Vec<Value> = diagnostic
+        .suggestions
+        .iter()
+        .map(|suggestion| {
+            let mut value = location(suggestion.span);
+            value["replacement"] = json!(suggestion.replacement);
+            value["message"] = json!(suggestion.message);
+            value["machine_applicable"] = json!(suggestion.machine_applicable);
+            value
+        })
+        .collect();
+
+    json!({
+        "file": name,
+        "severity": diagnostic.severity,
+        "code": diagnostic.code,
+        "message": diagnostic.message,
+        "spans": spans,
+        "notes": diagnostic.notes,
+        "suggestions": suggestions,
+    })
+    .to_string()
+}
+
+/// A message that isn't about any place in a script, in the same shape as [`json`].
+pub fn json_message(severity: Severity, file: Option<&str>, message: impl Display) -> String {
+    json!({
+        "file": file,
+        "severity": severity,
+        "code": null,
+        "message": message.to_string(),
+        "spans": [],
+        "notes": [],
+        "suggestions": [],
+    })
+    .to_string()
+}
+
+fn style(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "\x1B[1;31m",
+        Severity::Warning => "\x1B[1;33m",
+        Severity::Note => "\x1B[1;36m",
+    }
+}
+
+// Makes the spans of a diagnostic relative to the file it's about, and returns that file's
+// name and source. Labels and suggestions pointing into other files are dropped.
+pub fn localize<'a>(
+    diagnostic: &Diagnostic,
+    files: &'a SourceMap,
+) -> (Diagnostic, &'a str, &'a str) {
+    let mut diagnostic = diagnostic.clone();
+
+    // Every diagnostic should point into a loaded file, but the message is worth showing anyway
+    let Some(file) = files.find(diagnostic.primary_span.start) else {
+        diagnostic.primary_span = Span { start: 0, end: 0 };
+        diagnostic.labels.clear();
+        diagnostic.suggestions.clear();
+        return (diagnostic, "<unknown>", "");
+    };
+
+    diagnostic.primary_span = file.local(diagnostic.primary_span);
+    diagnostic.labels.retain(|label| file.contains(label.span));
+    for label in &mut diagnostic.labels {
+        label.span = file.local(label.span);
+    }
+    diagnostic
+        .suggestions
+        .retain(|suggestion| file.contains(suggestion.span));
+    for suggestion in &mut diagnostic.suggestions {
+        suggestion.span = file.local(suggestion.span);
+    }
+
+    (diagnostic, &file.name, &file.source)
+}
+
+fn marks<'a>(diagnostic: &'a Diagnostic, source: &str) -> Vec<Mark<'a>> {
+    let primary_label = diagnostic
+        .labels
+        .iter()
+        .find(|label| label.span == diagnostic.primary_span)
+        .map_or("", |label| label.message.as_str());
+
+    let mut marks = vec![mark(source, diagnostic.primary_span, true, primary_label)];
+    marks.extend(
+        diagnostic
+            .labels
+            .iter()
+            .filter(|label| label.span != diagnostic.primary_span)
+            .map(|label| mark(source, label.span, false, &label.message)),
+    );
+    marks
+}
+
+fn mark<'a>(source: &str, span: Span, primary: bool, message: &'a str) -> Mark<'a> {
+    let (line, _) = diagnostics::line_col(source, span.start);
+    let line_start = source[..span.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[span.start..]
+        .find('\n')
+        .map_or(source.len(), |i| span.start + i);
+    let line_end = if source[..line_end].ends_with('\r') && line_end > span.start {
+        line_end - 1
+    } else {
+        line_end
+    };
+
+    // Spans running over several lines are only underlined on the first one
+    let end = span.end.min(line_end);
+
+    Mark {
+        span,
+        line,
+        col: display_width(&source[line_start..span.start]),
+        width: display_width(&source[span.start..end]).max(1),
+        primary,
+        message,
+    }
+}
+
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
+
+fn expand_tabs(line: &str) -> String {
+    line.replace('\t', &" ".repeat(TAB_WIDTH))
+}
@@ -0,0 +1,189 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use rustyline::DefaultEditor;
+use rustyline::error::ReadlineError;
+
+use crate::emitter::Emitter;
+use cortado::diagnostics;
+use cortado::source_map::SourceMap;
+use cortado::suggest;
+use cortado::tokenizer::Token;
+
+const PROMPT: &str = "> ";
+const CONTINUATION_PROMPT: &str = "... ";
+// How entries are referred to in diagnostics
+const SOURCE_NAME: &str = "<repl>";
+const HISTORY_FILE: &str = ".cortado_history";
+
+const COMMANDS: [&str; 4] = [":help", ":load", ":tokens", ":quit"];
+const HELP: &str = "\
+:help           show this message
+:load <file>    load a script into the session
+:tokens <code>  print the tokens of <code>
+:quit           leave the session";
+
+enum Flow {
+    Continue,
+    Quit,
+}
+
+pub fn run(emitter: &Emitter, preload: &[PathBuf]) -> Result<(), String> {
+    let mut editor = DefaultEditor::new().map_err(|e| e.to_string())?;
+    let history = env::home_dir().map(|home| home.join(HISTORY_FILE));
+
+    if let Some(path) = &history {
+        // There is no history yet on the very first start, that's fine
+        let _ = editor.load_history(path);
+    }
+
+    for path in preload {
+        load(emitter, path);
+    }
+
+    let mut entry = String::new();
+
+    loop {
+        let prompt = if entry.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        };
+
+        match editor.readline(prompt) {
+            Ok(line) => {
+                if entry.is_empty() && line.trim().is_empty() {
+                    continue;
+                }
+
+                if entry.is_empty() && line.trim_start().starts_with(':') {
+                    let _ = editor.add_history_entry(line.as_str());
+
+                    match run_command(emitter, line.trim()) {
+                        Flow::Continue => continue,
+                        Flow::Quit => break,
+                    }
+                }
+
+                entry.push_str(&line);
+                entry.push('\n');
+
+                if is_incomplete(&entry) {
+                    continue;
+                }
+
+                let _ = editor.add_history_entry(entry.trim_end());
+                eval(emitter, &entry);
+                entry.clear();
+            }
+            // Ctrl-C only drops the entry being typed, Ctrl-D leaves the session
+            Err(ReadlineError::Interrupted) => entry.clear(),
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+
+    save_history(&mut editor, history)
+}
+
+fn run_command(emitter: &Emitter, line: &str) -> Flow {
+    let (name, arg) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let arg = arg.trim();
+
+    match name {
+        ":help" => println!("{HELP}"),
+        ":load" if arg.is_empty() => emitter.error(":load expects a path to a script"),
+        ":load" => load(emitter, Path::new(arg)),
+        ":tokens" => print_tokens(emitter, arg),
+        ":quit" => return Flow::Quit,
+        _ => match suggest::closest(name, COMMANDS) {
+            Some(command) => emitter.error(format_args!(
+                "unknown command '{name}' -- did you mean {command}?"
+            )),
+            None => emitter.error(format_args!(
+                "unknown command '{name}' -- type :help to see all commands"
+            )),
+        },
+    }
+
+    Flow::Continue
+}
+
+fn eval(emitter: &Emitter, entry: &str) {
+    // Until there is an evaluator, the "value" of an entry is its token stream
+    print_tokens(emitter, entry);
+}
+
+fn load(emitter: &Emitter, path: &Path) {
+    // Without an evaluator there is nothing to define in the session yet,
+    // so loading a script only reports whether it lexes
+    if let Err(err) = crate::lex_file(emitter, path) {
+        err.emit(emitter);
+    }
+}
+
+fn print_tokens(emitter: &Emitter, code: &str) {
+    match crate::lex_source(emitter, SOURCE_NAME, code) {
+        Ok(tokens) => {
+            for ta in tokens {
+                println!("{:?}", ta.token);
+            }
+        }
+        Err(err) => err.emit(emitter),
+    }
+}
+
+fn is_incomplete(entry: &str) -> bool {
+    // Lexing errors can't be fixed by typing more lines, so let `eval` report them
+    let mut files = SourceMap::default();
+    let id = files.add(SOURCE_NAME, entry);
+
+    let (tokens, diagnostics) = cortado::lex(files.file(id));
+    if diagnostics::has_errors(&diagnostics) {
+        return false;
+    }
+
+    let depth: isize = tokens
+        .iter()
+        .map(|ta| match ta.token {
+            Token::LParen | Token::LBrace | Token::LBracket => 1,
+            Token::RParen | Token::RBrace | Token::RBracket => -1,
+            _ => 0,
+        })
+        .sum();
+
+    depth > 0 || tokens.last().is_some_and(|ta| expects_operand(&ta.token))
+}
+
+fn expects_operand(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Dot
+            | Token::Comma
+            | Token::Assign
+            | Token::Colon
+            | Token::Less
+            | Token::And
+            | Token::Or
+            | Token::Plus
+            | Token::Minus
+            | Token::Star
+            | Token::Slash
+            | Token::Tilda
+            | Token::Le
+            | Token::Greater
+            | Token::Ge
+            | Token::Equal
+            | Token::NotEqual
+            | Token::RArrow
+    )
+}
+
+fn save_history(editor: &mut DefaultEditor, history: Option<PathBuf>) -> Result<(), String> {
+    match history {
+        Some(path) => editor
+            .save_history(&path)
+            .map_err(|e| format!("cannot save history to '{}': {}", path.display(), e)),
+        None => Ok(()),
+    }
+}
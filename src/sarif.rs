@@ -0,0 +1,106 @@
+use serde_json::{Value, json};
+
+use crate::render;
+use cortado::codes;
+use cortado::diagnostics::{self, Diagnostic, Severity};
+use cortado::source_map::SourceMap;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A diagnostic as a SARIF `result` object.
+pub fn result(diagnostic: &Diagnostic, files: &SourceMap) -> Value {
+    let (diagnostic, name, source) = render::localize(diagnostic, files);
+    let span = diagnostic.primary_span;
+    let (start_line, start_col) = diagnostics::line_col(source, span.start);
+    let (end_line, end_col) = diagnostics::line_col(source, span.end);
+
+    let level = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Note => "note",
+    };
+
+    let mut result = json!({
+        "level": level,
+        "message": { "text": diagnostic.message },
+    });
+    // Code from `-e`, stdin or the REPL has no file to point a location at
+    if let Some(uri) = uri(name) {
+        result["locations"] = json!([{
+            "physicalLocation": {
+                "artifactLocation": { "uri": uri },
+                "region": {
+                    "startLine": start_line,
+                    "startColumn": start_col,
+                    "endLine": end_line,
+                    "endColumn": end_col,
+                },
+            },
+        }]);
+    }
+    if let Some(code) = diagnostic.code {
+        result["ruleId"] = json!(code);
+    }
+    result
+}
+
+/// A whole SARIF log for one run of cortado, describing every code as a rule.
+pub fn log(results: Vec<Value>) -> String {
+    let rules: Vec<Value> = codes::all()
+        .iter()
+        .map(|explanation| {
+            json!({
+                "id": explanation.code,
+                "shortDescription": { "text": explanation.title },
+                "fullDescription": { "text": explanation.text },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "cortado",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            // Columns count characters, see `diagnostics::line_col`
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+    .to_string()
+}
+
+// A file's path as a URI: relative paths stay relative references, absolute ones become
+// `file:` URIs. Pseudo-files such as `<stdin>` have none.
+fn uri(name: &str) -> Option<String> {
+    if name.starts_with('<') && name.ends_with('>') {
+        return None;
+    }
+
+    let path = name.replace('\\', "/");
+    let (prefix, path) = if path.starts_with('/') {
+        ("file://", path)
+    } else if path.as_bytes().get(1) == Some(&b':') {
+        // A Windows path with a drive letter, such as C:/scripts
+        ("file:///", path)
+    } else {
+        ("", path)
+    };
+
+    let mut uri = String::from(prefix);
+    for (i, byte) in path.bytes().enumerate() {
+        let drive = prefix == "file:///" && i == 1;
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) || drive {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    Some(uri)
+}
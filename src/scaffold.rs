@@ -0,0 +1,75 @@
+use std::fs;
+use std::path::Path;
+
+use cortado::source_map::SourceMap;
+use cortado::tokenizer::Token;
+
+const MANIFEST: &str = "cortado.toml";
+const MAIN: &str = "main.cor";
+const TESTS: &str = "tests";
+
+const MAIN_SOURCE: &str = "\
+\"Hello, world!\".print
+";
+
+// Cortado doesn't write any build artifacts yet, so there is nothing to ignore
+const GITIGNORE: &str = "";
+
+/// Creates a new project named after the last component of `path`.
+pub fn new_project(path: &Path) -> Result<String, String> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("cannot name a project after '{}'", path.display()))?;
+
+    if !is_valid_name(name) {
+        return Err(format!(
+            "'{name}' is not a valid project name -- use letters, digits, '-' and '_', \
+            starting with a letter"
+        ));
+    }
+
+    if path.exists() {
+        return Err(format!("destination '{}' already exists", path.display()));
+    }
+
+    let write = |file: &Path, contents: &str| {
+        fs::write(file, contents).map_err(|e| format!("cannot write '{}': {}", file.display(), e))
+    };
+
+    fs::create_dir_all(path.join(TESTS))
+        .map_err(|e| format!("cannot create '{}': {}", path.display(), e))?;
+    write(&path.join(MANIFEST), &manifest(name))?;
+    write(&path.join(MAIN), MAIN_SOURCE)?;
+    write(&path.join(".gitignore"), GITIGNORE)?;
+
+    Ok(name.to_string())
+}
+
+fn is_valid_name(name: &str) -> bool {
+    // Project names are identifiers, so code can refer to them as is, but without the
+    // `?` and `!` endings, which would make odd directory and package names
+    if name.contains(['?', '!']) {
+        return false;
+    }
+
+    let mut files = SourceMap::default();
+    let id = files.add(name, name);
+
+    match cortado::lex(files.file(id)) {
+        (tokens, diagnostics) if diagnostics.is_empty() => {
+            matches!(tokens.as_slice(), [ta] if matches!(ta.token, Token::Ident(_)))
+        }
+        _ => false,
+    }
+}
+
+fn manifest(name: &str) -> String {
+    format!(
+        "\
+[package]
+name = \"{name}\"
+version = \"0.1.0\"
+"
+    )
+}
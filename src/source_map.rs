@@ -0,0 +1,69 @@
+//! Loaded files and the offset space their spans share.
+
+use alloc::{string::String, vec::Vec};
+
+use crate::tokenizer::Span;
+
+/// Identifies a file in a [`SourceMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileId(usize);
+
+/// A file loaded into a [`SourceMap`].
+pub struct SourceFile {
+    pub name: String,
+    pub source: String,
+    // Offset of the first byte in the map's offset space
+    pub start: usize,
+}
+
+impl SourceFile {
+    /// The part of a span inside this file, with offsets relative to the file.
+    pub fn local(&self, span: Span) -> Span {
+        let clamp = |offset: usize| offset.clamp(self.start, self.end()) - self.start;
+        Span {
+            start: clamp(span.start),
+            end: clamp(span.end),
+        }
+    }
+
+    pub fn contains(&self, span: Span) -> bool {
+        self.start <= span.start && span.end <= self.end()
+    }
+
+    fn end(&self) -> usize {
+        self.start + self.source.len()
+    }
+}
+
+/// Owns every loaded file. The files share one offset space, laid out one after another,
+/// so a span alone is enough to tell which file it points into.
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn add(&mut self, name: impl Into<String>, source: impl Into<String>) -> FileId {
+        // Leave a gap after each file, so that the end of one file isn't the start of the next
+        let start = self.files.last().map_or(0, |file| file.end() + 1);
+
+        self.files.push(SourceFile {
+            name: name.into(),
+            source: source.into(),
+            start,
+        });
+        FileId(self.files.len() - 1)
+    }
+
+    pub fn file(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0]
+    }
+
+    /// The file an offset points into.
+    pub fn find(&self, offset: usize) -> Option<&SourceFile> {
+        let after = self.files.partition_point(|file| file.start <= offset);
+        self.files[..after]
+            .last()
+            .filter(|file| offset <= file.end())
+    }
+}
@@ -1,12 +1,22 @@
-use std::str::Chars;
+//! Turns source text into tokens.
 
-#[derive(Debug, PartialEq)]
+use alloc::{format, string::String, vec::Vec};
+use core::str::Chars;
+
+use serde::Serialize;
+
+use crate::diagnostics::{Diagnostic, Suppression};
+
+/// A token of Cortado code. Literals keep their text exactly as written.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum Token {
     Ident(String),
     Int(String),
     Float(String),
     Str(String),
     Dot,
+    Ellipsis,
     Comma,
     LParen,
     RParen,
@@ -39,139 +49,193 @@ pub enum Token {
     KwDefault,
     KwTrue,
     KwFalse,
-
-    Error(String),
+    KwConst,
+    KwFor,
+    KwIn,
+    KwIf,
+    KwEnum,
+
+    // The problem itself is recorded in the tokenizer's diagnostics,
+    // and lexing goes on after the offending text
+    Error,
     Eof,
 }
 
-#[derive(Debug, PartialEq)]
+/// Words that can't be identifiers, and their tokens.
+pub const KEYWORDS: &[(&str, Token)] = &[
+    ("method", Token::KwMethod),
+    ("given", Token::KwGiven),
+    ("when", Token::KwWhen),
+    ("default", Token::KwDefault),
+    ("true", Token::KwTrue),
+    ("false", Token::KwFalse),
+    ("const", Token::KwConst),
+    ("for", Token::KwFor),
+    ("in", Token::KwIn),
+    ("if", Token::KwIf),
+    ("enum", Token::KwEnum),
+];
+
+/// Operators and punctuation, and their tokens. An operator is taken by the first
+/// entry it starts with, so longer operators come before their prefixes.
+pub const OPERATORS: &[(&str, Token)] = &[
+    ("...", Token::Ellipsis),
+    ("/=", Token::NotEqual),
+    ("<=", Token::Le),
+    (">=", Token::Ge),
+    ("==", Token::Equal),
+    ("=>", Token::RArrow),
+    (".", Token::Dot),
+    (",", Token::Comma),
+    ("(", Token::LParen),
+    (")", Token::RParen),
+    ("{", Token::LBrace),
+    ("}", Token::RBrace),
+    ("[", Token::LBracket),
+    ("]", Token::RBracket),
+    (":", Token::Colon),
+    (";", Token::Semicolon),
+    ("+", Token::Plus),
+    ("-", Token::Minus),
+    ("*", Token::Star),
+    ("/", Token::Slash),
+    ("~", Token::Tilda),
+    ("&", Token::And),
+    ("|", Token::Or),
+    ("<", Token::Less),
+    (">", Token::Greater),
+    ("=", Token::Assign),
+];
+
+/// Byte offsets into the source, `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A token and where it is: `line` and `col` are 1-based, `col` counts characters.
+#[derive(Debug, PartialEq, Serialize)]
 pub struct TokenAt {
-    token: Token,
-    line: usize,
-    col: usize,
+    pub token: Token,
+    pub line: usize,
+    pub col: usize,
+    pub span: Span,
 }
 
+/// Reads tokens one by one with [`next_token`](Tokenizer::next_token), recovering
+/// from errors and recording them as diagnostics.
 pub struct Tokenizer<'a> {
     input: Chars<'a>,
     last: char,
     eof: bool,
+    // Position of `last`
     line: usize,
     col: usize,
+    offset: usize,
+    // Line of the last token, to tell comments after code from comments on their own line
+    token_line: usize,
+    diagnostics: Vec<Diagnostic>,
+    suppressions: Vec<Suppression>,
 }
 
 impl<'a> Tokenizer<'a> {
     pub fn new(source: &'a str) -> Tokenizer<'a> {
+        Tokenizer::starting_at(source, 0)
+    }
+
+    /// A tokenizer whose spans count from `start` instead of 0, such as a file's
+    /// start in a `SourceMap`.
+    pub fn starting_at(source: &'a str, start: usize) -> Tokenizer<'a> {
+        let mut input = source.chars();
+        let (last, eof) = match input.next() {
+            Some(c) => (c, false),
+            None => (' ', true),
+        };
+
         Tokenizer {
-            input: source.chars(),
-            // Could be any character because it always gets skipped as `eof` is false
-            last: ' ',
-            eof: false,
+            input,
+            last,
+            eof,
             line: 1,
             col: 1,
+            offset: start,
+            token_line: 0,
+            diagnostics: Vec::new(),
+            suppressions: Vec::new(),
         }
     }
 
+    pub fn error_count(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    /// Diagnostics found so far, and the `# cortado: ignore` comments to filter them with.
+    pub fn into_diagnostics(self) -> (Vec<Diagnostic>, Vec<Suppression>) {
+        (self.diagnostics, self.suppressions)
+    }
+
     pub fn next_token(&mut self) -> TokenAt {
-        self.consume_whitespace();
-        self.consume_comment();
+        self.consume_whitespace_and_comments();
 
         let line = self.line;
         let col = self.col;
+        let start = self.offset;
 
         if self.eof {
             return TokenAt {
                 token: Token::Eof,
                 line,
                 col,
+                span: Span { start, end: start },
             };
         }
 
-        let mut advance = true;
-        let token = match self.last {
-            '.' => Token::Dot,
-            ',' => Token::Comma,
-            '(' => Token::LParen,
-            ')' => Token::RParen,
-            '{' => Token::LBrace,
-            '}' => Token::RBrace,
-            ':' => Token::Colon,
-            ';' => Token::Semicolon,
-            '+' => Token::Plus,
-            '-' => Token::Minus,
-            '*' => Token::Star,
-            '[' => Token::LBracket,
-            ']' => Token::RBracket,
-            '~' => Token::Tilda,
-            '/' => {
-                self.advance();
-
-                if self.is_current_char('=') {
-                    Token::NotEqual
-                } else {
-                    advance = false;
-                    Token::Slash
-                }
-            }
-            '&' => Token::And,
-            '|' => Token::Or,
-            '<' => {
-                self.advance();
-
-                if self.is_current_char('=') {
-                    Token::Le
-                } else {
-                    advance = false;
-                    Token::Less
+        let token =
+            if let Some((text, token)) = OPERATORS.iter().find(|(text, _)| self.looking_at(text)) {
+                for _ in text.chars() {
+                    self.advance();
                 }
-            }
-            '>' => {
+                token.clone()
+            } else if self.check(char::is_alphabetic) {
+                // Each of the following methods leave in `self.last`
+                // the character after the found token, so no need to advance
+                self.read_identifier_or_keyword()
+            } else if self.check(char::is_numeric) {
+                self.read_number_literal()
+            } else if self.is_current_char('"') {
+                self.read_string_literal()
+            } else {
+                let c = self.peek();
+                let span = Span {
+                    start: self.offset,
+                    end: self.offset + c.len_utf8(),
+                };
+                // Skip the character, so the next token starts after it
                 self.advance();
 
-                if self.is_current_char('=') {
-                    Token::Ge
-                } else {
-                    advance = false;
-                    Token::Greater
-                }
-            }
-            '=' => {
-                self.advance();
+                self.error(
+                    Diagnostic::error(format!("Unknown character '{c}'"), span)
+                        .with_code("E0001")
+                        .with_label(span, "not part of any token")
+                        .with_note(
+                            "Did you mean an operator, identifier or a string? \
+                        Try adding spaces, or wrap text in double quotes.",
+                        ),
+                )
+            };
 
-                if self.is_current_char('=') {
-                    Token::Equal
-                } else if self.is_current_char('>') {
-                    Token::RArrow
-                } else {
-                    advance = false;
-                    Token::Assign
-                }
-            }
-            _ => {
-                // Each of the following methods leave in `self.last`
-                // the character after the found token, so no need to advance
-                advance = false;
-
-                if self.check(char::is_alphabetic) {
-                    self.read_identifier_or_keyword()
-                } else if self.check(char::is_numeric) {
-                    self.read_number_literal()
-                } else if self.is_current_char('"') {
-                    self.read_string_literal()
-                } else {
-                    self.error(&format!(
-                        "Unknown character '{}' -- did you mean an operator, \
-                    identifier or a string? Try adding spaces, or wrap text in double quotes.",
-                        self.peek()
-                    ))
-                }
-            }
-        };
+        self.token_line = line;
 
-        if advance {
-            self.advance();
+        TokenAt {
+            token,
+            line,
+            col,
+            span: Span {
+                start,
+                end: self.offset,
+            },
         }
-
-        TokenAt { token, line, col }
     }
 
     fn peek(&self) -> char {
@@ -183,20 +247,17 @@ impl<'a> Tokenizer<'a> {
             return;
         }
 
+        self.offset += self.last.len_utf8();
+        if self.last == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+
         match self.input.next() {
-            Some(c) => {
-                self.last = c;
-
-                if self.last == '\n' {
-                    self.line += 1;
-                    self.col = 1;
-                } else {
-                    self.col += 1;
-                }
-            }
-            None => {
-                self.eof = true;
-            }
+            Some(c) => self.last = c,
+            None => self.eof = true,
         }
     }
 
@@ -208,31 +269,45 @@ impl<'a> Tokenizer<'a> {
             self.advance();
         }
 
-        // TODO: replace it with a HashMap
-        match buf.as_str() {
-            "method" => Token::KwMethod,
-            "given" => Token::KwGiven,
-            "when" => Token::KwWhen,
-            "default" => Token::KwDefault,
-            "true" => Token::KwTrue,
-            "false" => Token::KwFalse,
-            _ => Token::Ident(buf),
+        match KEYWORDS.iter().find(|(keyword, _)| *keyword == buf) {
+            Some((_, token)) => token.clone(),
+            None => Token::Ident(buf),
         }
     }
 
-    fn consume_whitespace(&mut self) {
-        while self.check(char::is_whitespace) {
-            self.advance();
-        }
-    }
+    fn consume_whitespace_and_comments(&mut self) {
+        loop {
+            while self.check(char::is_whitespace) {
+                self.advance();
+            }
 
-    fn consume_comment(&mut self) {
-        if self.is_current_char('#') {
-            self.advance();
+            if !self.is_current_char('#') {
+                return;
+            }
 
-            while !self.is_current_char('\n') {
+            let start = self.offset;
+            let line = self.line;
+            let mut comment = String::new();
+            while !self.eof && !self.is_current_char('\n') {
+                comment.push(self.last);
                 self.advance();
             }
+
+            if let Some(codes) = suppressed_codes(&comment) {
+                self.suppressions.push(Suppression {
+                    span: Span {
+                        start,
+                        end: start + comment.trim_end().len(),
+                    },
+                    // A comment after code is about its own line, otherwise about the next one
+                    line: if self.token_line == line {
+                        line
+                    } else {
+                        line + 1
+                    },
+                    codes,
+                });
+            }
         }
     }
 
@@ -260,15 +335,37 @@ impl<'a> Tokenizer<'a> {
 
     fn read_string_literal(&mut self) -> Token {
         let mut buf = String::new();
+        let start = self.offset;
 
         // Skip the first quote without checking it (checking is done in `tokenize`)
         self.advance();
 
         while !self.eof && !self.is_current_char('"') {
             if self.last == '\n' {
+                // The closing quote goes before the line ending, whichever kind it is
+                let carriage_return = usize::from(buf.ends_with('\r'));
+                let span = Span {
+                    start,
+                    end: self.offset,
+                };
+
                 return self.error(
-                    "Unterminated string -- found a newline before \
-                the closing quote. Keep strings on one line.",
+                    Diagnostic::error(
+                        "Unterminated string -- found a newline before the closing quote",
+                        span,
+                    )
+                    .with_code("E0002")
+                    .with_label(span, "this string is never closed")
+                    .with_note("Keep strings on one line.")
+                    .with_suggestion(
+                        Span {
+                            start: self.offset - carriage_return,
+                            end: self.offset - carriage_return,
+                        },
+                        "\"",
+                        "close the string",
+                        true,
+                    ),
                 );
             }
 
@@ -277,9 +374,27 @@ impl<'a> Tokenizer<'a> {
         }
 
         if self.eof {
+            let span = Span {
+                start,
+                end: self.offset,
+            };
+
             return self.error(
-                "Unterminated string -- reached end of input before closing quote. \
-            Add a closing '\"'",
+                Diagnostic::error(
+                    "Unterminated string -- reached end of input before the closing quote",
+                    span,
+                )
+                .with_code("E0002")
+                .with_label(span, "this string is never closed")
+                .with_suggestion(
+                    Span {
+                        start: self.offset,
+                        end: self.offset,
+                    },
+                    "\"",
+                    "close the string",
+                    true,
+                ),
             );
         }
 
@@ -289,6 +404,17 @@ impl<'a> Tokenizer<'a> {
         Token::Str(buf)
     }
 
+    fn looking_at(&self, s: &str) -> bool {
+        let mut chars = s.chars();
+        chars.next().is_some_and(|c| self.is_current_char(c)) && self.followed_by(chars.as_str())
+    }
+
+    fn followed_by(&self, s: &str) -> bool {
+        // Looks past `self.last` without consuming anything
+        let mut rest = self.input.clone();
+        s.chars().all(|c| rest.next() == Some(c))
+    }
+
     fn check<F>(&self, pred: F) -> bool
     where
         F: Fn(char) -> bool,
@@ -310,18 +436,38 @@ impl<'a> Tokenizer<'a> {
         self.check(|c| c.is_numeric() || c == '_')
     }
 
-    fn error(&self, msg: &str) -> Token {
-        Token::Error(format!("{}:{}: {}", self.line, self.col, msg))
+    fn error(&mut self, diagnostic: Diagnostic) -> Token {
+        self.diagnostics.push(diagnostic);
+        Token::Error
     }
 }
 
+// Codes listed by a `# cortado: ignore W0001, W0002` comment
+fn suppressed_codes(comment: &str) -> Option<Vec<String>> {
+    let directive = comment
+        .strip_prefix('#')?
+        .trim_start()
+        .strip_prefix("cortado:")?
+        .trim_start()
+        .strip_prefix("ignore")
+        .filter(|codes| codes.starts_with(char::is_whitespace))?;
+
+    let codes: Vec<String> = directive
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|code| !code.is_empty())
+        .map(str::to_uppercase)
+        .collect();
+    (!codes.is_empty()).then_some(codes)
+}
+
 impl<'a> Iterator for Tokenizer<'a> {
     type Item = TokenAt;
 
     fn next(&mut self) -> Option<Self::Item> {
         let ta = self.next_token();
 
-        if matches!(ta.token, Token::Eof | Token::Error(_)) {
+        // Errors are yielded too: the tokenizer recovers from them and carries on
+        if ta.token == Token::Eof {
             None
         } else {
             Some(ta)
@@ -0,0 +1,66 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use notify::{Event, RecursiveMode, Watcher};
+
+// Editors tend to save a file in several steps, wait for them to settle down
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Runs `action` once, then again every time one of `files` changes.
+/// Only returns if watching itself fails.
+pub fn watch<F>(files: &[PathBuf], mut action: F) -> Result<(), String>
+where
+    F: FnMut(),
+{
+    let files = files
+        .iter()
+        .map(|path| {
+            fs::canonicalize(path).map_err(|e| format!("cannot watch '{}': {}", path.display(), e))
+        })
+        .collect::<Result<HashSet<_>, _>>()?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| e.to_string())?;
+
+    // Watching the directories rather than the files themselves keeps working
+    // when an editor saves by replacing the file
+    let dirs: HashSet<&Path> = files.iter().filter_map(|path| path.parent()).collect();
+    for dir in dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("cannot watch '{}': {}", dir.display(), e))?;
+    }
+
+    loop {
+        clear_screen();
+        action();
+
+        loop {
+            let event: Event = match rx.recv() {
+                Ok(event) => event.map_err(|e| e.to_string())?,
+                Err(_) => return Err("file watcher stopped unexpectedly".to_string()),
+            };
+
+            if !event.kind.is_access() && event.paths.iter().any(|path| files.contains(path)) {
+                break;
+            }
+        }
+
+        thread::sleep(DEBOUNCE);
+        while rx.try_recv().is_ok() {}
+    }
+}
+
+fn clear_screen() {
+    // Escape codes would only get in the way of whatever reads the output otherwise
+    if !io::stdout().is_terminal() {
+        return;
+    }
+    print!("\x1B[2J\x1B[1;1H");
+    let _ = io::stdout().flush();
+}
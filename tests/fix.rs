@@ -0,0 +1,152 @@
+//! Applying fixes and showing them as diffs.
+
+use cortado::diagnostics::Suggestion;
+use cortado::fix;
+use cortado::source_map::SourceMap;
+use cortado::tokenizer::Span;
+
+fn edit(start: usize, end: usize, replacement: &str) -> Suggestion {
+    Suggestion {
+        span: Span { start, end },
+        replacement: replacement.to_string(),
+        message: "fix it".to_string(),
+        machine_applicable: true,
+    }
+}
+
+// Lexes `source` and applies every fix the diagnostics suggest
+fn fix_source(source: &str) -> (String, usize) {
+    let mut files = SourceMap::default();
+    let id = files.add("fix.cor", source);
+    let (_, diagnostics) = cortado::lex(files.file(id));
+    let suggestions: Vec<Suggestion> = diagnostics
+        .into_iter()
+        .flat_map(|diagnostic| diagnostic.suggestions)
+        .collect();
+    fix::apply(source, &suggestions)
+}
+
+#[test]
+fn apply_edits_in_order_of_position() {
+    let suggestions = [edit(8, 9, "c"), edit(0, 1, "a")];
+    assert_eq!(
+        fix::apply("x = 1 + y", &suggestions),
+        ("a = 1 + c".to_string(), 2)
+    );
+}
+
+#[test]
+fn apply_skips_edits_overlapping_an_earlier_one() {
+    let suggestions = [edit(0, 5, "first"), edit(3, 7, "second"), edit(5, 5, "!")];
+    assert_eq!(
+        fix::apply("abcdefgh", &suggestions),
+        ("first!fgh".to_string(), 2)
+    );
+}
+
+#[test]
+fn apply_skips_suggestions_that_need_review() {
+    let suggestions = [Suggestion {
+        machine_applicable: false,
+        ..edit(0, 1, "y")
+    }];
+    assert_eq!(fix::apply("x", &suggestions), ("x".to_string(), 0));
+}
+
+#[test]
+fn apply_inserts_at_end_of_input() {
+    assert_eq!(fix_source("x = \"open"), ("x = \"open\"".to_string(), 1));
+}
+
+#[test]
+fn apply_closes_strings_before_crlf() {
+    assert_eq!(
+        fix_source("x = \"open\r\ny = 1\r\n"),
+        ("x = \"open\"\r\ny = 1\r\n".to_string(), 1)
+    );
+}
+
+#[test]
+fn apply_removes_unused_suppressions() {
+    assert_eq!(
+        fix_source("x = 1 # cortado: ignore W0001\n# cortado: ignore W0001\ny = 2\n"),
+        ("x = 1\ny = 2\n".to_string(), 2)
+    );
+}
+
+#[test]
+fn diff_shows_changed_lines_with_context() {
+    assert_eq!(
+        fix::diff(
+            "a.cor",
+            "1\n2\n3\n4\nb\n5\n6\n7\n8\n",
+            "1\n2\n3\n4\nB\n5\n6\n7\n8\n"
+        ),
+        "--- a.cor\n+++ a.cor\n@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-b\n+B\n 5\n 6\n 7\n"
+    );
+}
+
+#[test]
+fn diff_splits_changes_far_apart_into_hunks() {
+    let old = "a\n1\n2\n3\n4\n5\n6\n7\nb\n";
+    let new = "A\n1\n2\n3\n4\n5\n6\n7\nB\n";
+    assert_eq!(
+        fix::diff("a.cor", old, new),
+        "--- a.cor\n+++ a.cor\n@@ -1,4 +1,4 @@\n-a\n+A\n 1\n 2\n 3\n@@ -6,4 +6,4 @@\n 5\n 6\n 7\n-b\n+B\n"
+    );
+}
+
+#[test]
+fn diff_of_pure_insertion() {
+    assert_eq!(
+        fix::diff("a.cor", "a\nc\n", "a\nb\nc\n"),
+        "--- a.cor\n+++ a.cor\n@@ -1,2 +1,3 @@\n a\n+b\n c\n"
+    );
+    assert_eq!(
+        fix::diff("a.cor", "", "a\n"),
+        "--- a.cor\n+++ a.cor\n@@ -0,0 +1,1 @@\n+a\n"
+    );
+}
+
+#[test]
+fn diff_of_pure_deletion() {
+    assert_eq!(
+        fix::diff("a.cor", "a\nb\nc\n", "a\nc\n"),
+        "--- a.cor\n+++ a.cor\n@@ -1,3 +1,2 @@\n a\n-b\n c\n"
+    );
+    assert_eq!(
+        fix::diff("a.cor", "a\n", ""),
+        "--- a.cor\n+++ a.cor\n@@ -1,1 +0,0 @@\n-a\n"
+    );
+}
+
+#[test]
+fn diff_of_equal_sources_has_no_hunks() {
+    assert_eq!(fix::diff("a.cor", "a\n", "a\n"), "--- a.cor\n+++ a.cor\n");
+}
+
+#[test]
+fn diff_keeps_crlf_line_endings() {
+    assert_eq!(
+        fix::diff("a.cor", "a\r\nb\r\n", "a\r\nB\r\n"),
+        "--- a.cor\n+++ a.cor\n@@ -1,2 +1,2 @@\n a\r\n-b\r\n+B\r\n"
+    );
+}
+
+#[test]
+fn diff_marks_a_missing_final_newline() {
+    assert_eq!(
+        fix::diff("a.cor", "a\nb", "a\nb\""),
+        "--- a.cor\n+++ a.cor\n@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+b\"\n\\ No newline at end of file\n"
+    );
+}
+
+#[test]
+fn diff_numbers_lines_after_an_unchanged_start() {
+    let old = "x\n".repeat(10_000) + "a\n" + &"y\n".repeat(10_000);
+    let new = "x\n".repeat(10_000) + "b\n" + &"y\n".repeat(10_000);
+    assert_eq!(
+        fix::diff("a.cor", &old, &new),
+        "--- a.cor\n+++ a.cor\n@@ -9998,7 +9998,7 @@\n x\n x\n x\n-a\n+b\n y\n y\n y\n"
+    );
+}
@@ -0,0 +1,116 @@
+//! Golden tests for the tokenizer: every `tests/lexer/*.cor` fixture is lexed,
+//! and the dump of its tokens, diagnostics and their fixes is compared with the `.tokens`
+//! file next to it. Run with `BLESS=1` to write the dumps after a deliberate change.
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use cortado::diagnostics::{self, Diagnostic};
+use cortado::source_map::SourceMap;
+use cortado::tokenizer::Span;
+
+const FIXTURES: &str = "tests/lexer";
+
+#[test]
+fn lexer_fixtures() {
+    let bless = env::var_os("BLESS").is_some();
+    let mut fixtures: Vec<_> = fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join(FIXTURES))
+        .expect("fixtures directory")
+        .map(|entry| entry.expect("fixture entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "cor"))
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "no fixtures in {FIXTURES}");
+
+    let mut failures = Vec::new();
+    for fixture in &fixtures {
+        let source = fs::read_to_string(fixture).expect("readable fixture");
+        let actual = dump(&source);
+        let expected_path = fixture.with_extension("tokens");
+
+        if bless {
+            fs::write(&expected_path, &actual).expect("writable expected dump");
+            continue;
+        }
+
+        match fs::read_to_string(&expected_path) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => failures.push(format!(
+                "{} changed:\n{}",
+                fixture.display(),
+                diff(&expected, &actual)
+            )),
+            Err(_) => failures.push(format!(
+                "{} has no expected dump, run with BLESS=1 to create it",
+                fixture.display()
+            )),
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+fn dump(source: &str) -> String {
+    let mut files = SourceMap::default();
+    let id = files.add("fixture.cor", source);
+    let (tokens, diagnostics) = cortado::lex(files.file(id));
+
+    let mut out = String::new();
+    for ta in tokens {
+        let pos = format!("{}:{}", ta.line, ta.col);
+        let _ = writeln!(out, "{pos:<8}{:?}", ta.token);
+    }
+    for diagnostic in &diagnostics {
+        let _ = writeln!(out, "{}", describe(source, diagnostic));
+        for suggestion in &diagnostic.suggestions {
+            let _ = writeln!(
+                out,
+                "  help {} {:?} -> {:?}",
+                position(source, suggestion.span),
+                &source[suggestion.span.start..suggestion.span.end],
+                suggestion.replacement
+            );
+        }
+    }
+    out
+}
+
+fn describe(source: &str, diagnostic: &Diagnostic) -> String {
+    format!(
+        "{}[{}] {} {}",
+        diagnostic.severity,
+        diagnostic.code.unwrap_or("-"),
+        position(source, diagnostic.primary_span),
+        diagnostic.message
+    )
+}
+
+fn position(source: &str, span: Span) -> String {
+    let (line, col) = diagnostics::line_col(source, span.start);
+    let (end_line, end_col) = diagnostics::line_col(source, span.end);
+    format!("{line}:{col}-{end_line}:{end_col}")
+}
+
+// Lines only in `expected` start with `-`, lines only in `actual` with `+`
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut out = String::new();
+
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e == a => {}
+            (e, a) => {
+                if let Some(e) = e {
+                    let _ = writeln!(out, "  {:>4} - {e}", i + 1);
+                }
+                if let Some(a) = a {
+                    let _ = writeln!(out, "  {:>4} + {a}", i + 1);
+                }
+            }
+        }
+    }
+    out
+}
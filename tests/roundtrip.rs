@@ -0,0 +1,175 @@
+//! Property tests for the tokenizer: random token sequences are printed as source
+//! and lexed back. Cases come from a fixed seed, so failures reproduce; a failing
+//! case is shrunk to the fewest tokens that still fail before it's reported.
+
+use cortado::diagnostics;
+use cortado::source_map::SourceMap;
+use cortado::tokenizer::{KEYWORDS, OPERATORS, Token, TokenAt};
+
+const CASES: u64 = 500;
+const MAX_TOKENS: usize = 40;
+
+// xorshift64*, good enough to pick tokens and no dependency to fetch
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        // Zero is the one state xorshift never leaves
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())]
+    }
+}
+
+const LETTERS: &[char] = &['a', 'b', 'x', 'z', 'A', 'Q', 'é', 'ß', 'λ', 'Ж'];
+const DIGITS: &[char] = &['0', '1', '5', '9', '٣'];
+const IDENTIFIER_TAIL: &[char] = &['a', 'k', 'Z', 'ü', '0', '7', '?', '!', '-', '_'];
+const STRING_CHARS: &[char] = &['a', ' ', '#', '.', '\\', '\'', '\t', '☕', '=', '1'];
+const SEPARATORS: &[&str] = &[" ", " ", " ", "  ", "\t", "\n", "\r\n", " # note\n"];
+
+fn token(rng: &mut Rng) -> Token {
+    match rng.below(6) {
+        0 => KEYWORDS[rng.below(KEYWORDS.len())].1.clone(),
+        1 => OPERATORS[rng.below(OPERATORS.len())].1.clone(),
+        2 => Token::Ident(identifier(rng)),
+        3 => Token::Int(digits(rng)),
+        4 => Token::Float(format!("{}.{}", digits(rng), digits(rng))),
+        _ => Token::Str((0..rng.below(8)).map(|_| rng.pick(STRING_CHARS)).collect()),
+    }
+}
+
+fn identifier(rng: &mut Rng) -> String {
+    let mut name = String::from(rng.pick(LETTERS));
+    for _ in 0..rng.below(6) {
+        name.push(rng.pick(IDENTIFIER_TAIL));
+    }
+    // A keyword would come back as a keyword
+    if KEYWORDS.iter().any(|(keyword, _)| *keyword == name) {
+        name.push('_');
+    }
+    name
+}
+
+fn digits(rng: &mut Rng) -> String {
+    let mut digits = String::from(rng.pick(DIGITS));
+    for _ in 0..rng.below(4) {
+        digits.push(if rng.below(5) == 0 {
+            '_'
+        } else {
+            rng.pick(DIGITS)
+        });
+    }
+    digits
+}
+
+fn text(token: &Token) -> String {
+    match token {
+        Token::Ident(text) | Token::Int(text) | Token::Float(text) => text.clone(),
+        Token::Str(text) => format!("\"{text}\""),
+        _ => KEYWORDS
+            .iter()
+            .chain(OPERATORS)
+            .find(|(_, t)| t == token)
+            .map(|(text, _)| text.to_string())
+            .expect("every other token is a keyword or an operator"),
+    }
+}
+
+fn print(tokens: &[Token], separators: &[&str]) -> String {
+    tokens
+        .iter()
+        .zip(separators)
+        .map(|(token, separator)| text(token) + separator)
+        .collect()
+}
+
+fn lex(source: &str) -> (Vec<TokenAt>, usize) {
+    let mut files = SourceMap::default();
+    let id = files.add("case.cor", source);
+    let (tokens, diagnostics) = cortado::lex(files.file(id));
+    (tokens, diagnostics.len())
+}
+
+// Runs `property` on generated cases, panicking with the smallest failing case found
+fn check(property: impl Fn(&[Token], &str) -> Result<(), String>) {
+    for seed in 0..CASES {
+        let mut rng = Rng::new(seed);
+        let count = rng.below(MAX_TOKENS) + 1;
+        let mut tokens: Vec<Token> = (0..count).map(|_| token(&mut rng)).collect();
+        let mut separators: Vec<&str> = (0..count).map(|_| rng.pick(SEPARATORS)).collect();
+
+        let Err(mut failure) = property(&tokens, &print(&tokens, &separators)) else {
+            continue;
+        };
+
+        // Drop tokens one at a time for as long as the property keeps failing
+        let mut i = 0;
+        while i < tokens.len() {
+            let (removed, separator) = (tokens.remove(i), separators.remove(i));
+            match property(&tokens, &print(&tokens, &separators)) {
+                Err(smaller) => failure = smaller,
+                Ok(()) => {
+                    tokens.insert(i, removed);
+                    separators.insert(i, separator);
+                    i += 1;
+                }
+            }
+        }
+
+        panic!(
+            "seed {seed} fails on {:?}:\n{failure}",
+            print(&tokens, &separators)
+        );
+    }
+}
+
+#[test]
+fn printed_tokens_lex_back_the_same() {
+    check(|printed, source| {
+        let (tokens, errors) = lex(source);
+        let relexed: Vec<Token> = tokens.into_iter().map(|ta| ta.token).collect();
+
+        if errors > 0 {
+            Err(format!("{errors} unexpected diagnostics"))
+        } else if relexed != printed {
+            Err(format!("expected {printed:?}\n     got {relexed:?}"))
+        } else {
+            Ok(())
+        }
+    });
+}
+
+#[test]
+fn tokens_span_their_own_text() {
+    check(|_, source| {
+        for ta in lex(source).0 {
+            let spanned = &source[ta.span.start..ta.span.end];
+            if spanned != text(&ta.token) {
+                return Err(format!("{:?} spans {spanned:?}", ta.token));
+            }
+            if diagnostics::line_col(source, ta.span.start) != (ta.line, ta.col) {
+                return Err(format!(
+                    "{:?} is at {}:{}, but its span starts at {:?}",
+                    ta.token,
+                    ta.line,
+                    ta.col,
+                    diagnostics::line_col(source, ta.span.start)
+                ));
+            }
+        }
+        Ok(())
+    });
+}